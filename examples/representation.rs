@@ -0,0 +1,104 @@
+// Compares the nested maps the solvers used to build with the dense instance,
+// in heap memory and build time:
+//     cargo run --release --example representation [INPUT]
+// INPUT defaults to resources/trending_today.in
+extern crate videos;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use videos::types::*;
+use videos::algo::segmented_gain;
+use videos::context::Context;
+use videos::dense::DenseInstance;
+use videos::parser::parse;
+
+// Counts the bytes allocated on the heap, and their peak since the last reset
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn measure<T, F: FnOnce() -> T>(name: &str, build: F) -> T {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let start = Instant::now();
+    let built = build();
+    println!("{:<24} {:>8} ms {:>14} bytes held {:>14} bytes peak", name, start.elapsed().as_millis(),
+             ALLOCATED.load(Ordering::Relaxed) - before, PEAK.load(Ordering::Relaxed) - before);
+    built
+}
+
+type NestedInstance = (BTreeMap<VideoId, BTreeMap<EndpointId, i64>>, BTreeMap<CacheId, BTreeMap<EndpointId, i32>>);
+
+// Requests per video x endpoint and latencies per cache x endpoint
+fn nested_instance(instance: &Instance) -> NestedInstance {
+    let mut video_endpoint_to_request: BTreeMap<VideoId, BTreeMap<EndpointId, i64>> = BTreeMap::new();
+    for request in &instance.requests {
+        *video_endpoint_to_request.entry(request.video_id).or_default()
+            .entry(request.endpoint_id).or_insert(0) += request.count as i64;
+    }
+    let mut cache_endpoint_to_latency: BTreeMap<CacheId, BTreeMap<EndpointId, i32>> = BTreeMap::new();
+    for endpoint in &instance.endpoints {
+        for (&cache_id, &latency) in &endpoint.cache_to_latency {
+            cache_endpoint_to_latency.entry(cache_id).or_default().insert(endpoint.id, latency);
+        }
+    }
+    (video_endpoint_to_request, cache_endpoint_to_latency)
+}
+
+// One map per video x cache
+fn nested_segmented_gain(instance: &Instance, nested: &NestedInstance) -> BTreeMap<(VideoId, CacheId), BTreeMap<EndpointId, i64>> {
+    let (ref video_endpoint_to_request, ref cache_endpoint_to_latency) = *nested;
+    let no_requests: BTreeMap<EndpointId, i64> = BTreeMap::new();
+    let no_endpoints: BTreeMap<EndpointId, i32> = BTreeMap::new();
+    let mut gains: BTreeMap<(VideoId, CacheId), BTreeMap<EndpointId, i64>> = BTreeMap::new();
+    for video in &instance.videos {
+        let endpoint_to_request = video_endpoint_to_request.get(&video.id).unwrap_or(&no_requests);
+        for cache_id in instance.cache_info.ids() {
+            let gain_map = gains.entry((video.id, cache_id)).or_default();
+            for (endpoint_id, &latency) in cache_endpoint_to_latency.get(&cache_id).unwrap_or(&no_endpoints) {
+                if let Some(&requests) = endpoint_to_request.get(endpoint_id) {
+                    let datacenter_latency = instance.endpoints[endpoint_id.index()].datacenter_latency;
+                    gain_map.insert(*endpoint_id, (datacenter_latency - latency) as i64 * requests);
+                }
+            }
+        }
+    }
+    gains
+}
+
+fn main() {
+    let input = env::args().nth(1).unwrap_or(String::from("resources/trending_today.in"));
+    let contents = fs::read_to_string(&input).unwrap_or_else(|err| panic!("{}: {}", input, err));
+    let (cache_info, videos, endpoints, requests) = parse(&contents).expect("Unable to parse input");
+    let instance = Instance::new(cache_info, videos, endpoints, requests);
+    println!("{}", input);
+
+    let nested = measure("nested instance", || nested_instance(&instance));
+    measure("dense instance", || DenseInstance::from_instance(&instance));
+    measure("nested segmented gain", || nested_segmented_gain(&instance, &nested));
+    measure("dense segmented gain", || {
+        segmented_gain(&Context::new(), &instance.cache_info, &instance.videos, instance.endpoints.clone(),
+                       instance.requests.clone())
+    });
+}
@@ -1,8 +1,17 @@
 use types::*;
+use dense::DenseInstance;
+use placement::Placement;
+use context::Context;
+use progress::Verbosity;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::collections::BinaryHeap;
 use std::cmp::max;
+use std::cmp::Reverse;
+use std::mem;
+use std::ops::Range;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Mode {
     Dummy,
     CacheSpreading,
@@ -11,88 +20,94 @@ pub enum Mode {
     DescentCost,
     DescentAudience,
     BestVideo,
-    DescentAmend
+    DescentAmend,
+    DescentThroughput
 }
 
-pub fn algo(mode: Mode, cache_info: CacheInfo, videos: Vec<Video>, endpoints: Vec<Endpoint>,
-            requests: Vec<Request>) -> BTreeMap<i32, BTreeSet<i32>> {
-    match mode {
-        Mode::Dummy => dummy_algo(),
-        Mode::CacheSpreading => cache_spreading(cache_info, videos),
-        Mode::CacheFilling => cache_filling(cache_info, videos),
-        Mode::Descent => descent(GainMode::PureGain, cache_info, videos, endpoints, requests),
-        Mode::DescentCost => descent(GainMode::GainOverCost, cache_info, videos, endpoints, requests),
-        Mode::DescentAudience => descent(GainMode::GainOverAudience, cache_info, videos, endpoints, requests),
-        Mode::BestVideo => best_video(cache_info, videos, endpoints, requests),
-        Mode::DescentAmend => descent_amend(cache_info, videos, endpoints, requests)
+impl Mode {
+    // Every registered strategy, in the order the portfolio runs them
+    pub fn all() -> Vec<Mode> {
+        vec![Mode::Dummy, Mode::CacheSpreading, Mode::CacheFilling, Mode::Descent, Mode::DescentCost,
+             Mode::DescentAudience, Mode::BestVideo, Mode::DescentAmend, Mode::DescentThroughput]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Mode::Dummy => "dummy",
+            Mode::CacheSpreading => "cache-spreading",
+            Mode::CacheFilling => "cache-filling",
+            Mode::Descent => "descent",
+            Mode::DescentCost => "descent-cost",
+            Mode::DescentAudience => "descent-audience",
+            Mode::BestVideo => "best-video",
+            Mode::DescentAmend => "descent-amend",
+            Mode::DescentThroughput => "descent-throughput"
+        }
     }
-}
 
-fn dummy_algo() -> BTreeMap<i32, BTreeSet<i32>> {
-    BTreeMap::new()
+    pub fn from_name(name: &str) -> Option<Mode> {
+        Mode::all().into_iter().find(|mode| mode.name() == name)
+    }
 }
 
-struct FilledCache {
-    videos: BTreeSet<i32>,
-    remaining_capacity: i32
+// Long-running modes stop when the context tells them to, returning the placement built so far
+pub fn algo(mode: Mode, context: &Context, cache_info: CacheInfo, videos: Vec<Video>, endpoints: Vec<Endpoint>,
+            requests: Vec<Request>) -> Placement {
+    let initial = Placement::new(&cache_info, &videos);
+    algo_from(mode, context, &initial, cache_info, videos, endpoints, requests)
 }
 
-impl FilledCache {
-    fn new(capacity: i32) -> FilledCache {
-        FilledCache {
-            videos: BTreeSet::new(),
-            remaining_capacity: capacity
+// Warm start: every mode keeps the videos of the initial placement and only adds
+// new ones, so the result is never worse than the initial placement. The initial
+// placement must be built for the instance.
+pub fn algo_from(mode: Mode, context: &Context, initial: &Placement, cache_info: CacheInfo,
+                 videos: Vec<Video>, endpoints: Vec<Endpoint>, requests: Vec<Request>) -> Placement {
+    match mode {
+        Mode::Dummy => dummy_algo(initial),
+        Mode::CacheSpreading => cache_spreading(initial, cache_info, videos),
+        Mode::CacheFilling => cache_filling(initial, cache_info, videos),
+        Mode::Descent => descent(GainMode::PureGain, context, initial, cache_info, videos, endpoints, requests),
+        Mode::DescentCost => descent(GainMode::GainOverCost, context, initial, cache_info, videos, endpoints, requests),
+        Mode::DescentAudience => {
+            descent(GainMode::GainOverAudience, context, initial, cache_info, videos, endpoints, requests)
         }
+        Mode::BestVideo => best_video(context, initial, cache_info, videos, endpoints, requests),
+        Mode::DescentAmend => descent_amend(context, initial, cache_info, videos, endpoints, requests),
+        Mode::DescentThroughput => descent_throughput(context, initial, cache_info, videos, endpoints, requests)
     }
+}
 
-    fn add_video(&mut self, ref video: &Video) -> bool {
-        if video.size <= self.remaining_capacity {
-            self.remaining_capacity -= video.size;
-            self.videos.insert(video.id);
-            true
-        } else {
-            false
-        }
-    }
+fn dummy_algo(initial: &Placement) -> Placement {
+    initial.clone()
 }
 
-fn cache_spreading(cache_info: CacheInfo, videos: Vec<Video>) -> BTreeMap<i32, BTreeSet<i32>> {
-    let mut filled: BTreeMap<i32, FilledCache>
-        = (0..cache_info.count).map(|id| (id, FilledCache::new(cache_info.capacity))).collect();
+fn cache_spreading(initial: &Placement, cache_info: CacheInfo, videos: Vec<Video>) -> Placement {
+    let mut placement = initial.clone();
 
     let mut current_cache: i32 = 0;
     for video in videos {
-        filled.get_mut(&current_cache).map(|cache| cache.add_video(&video));
+        placement.try_add(CacheId(current_cache), video.id);
         current_cache = (current_cache + 1) % cache_info.count;
     }
 
-    let returned: BTreeMap<i32, BTreeSet<i32>> = filled.iter().map(|(cache_id, cache)| {
-        let videos: BTreeSet<i32> = cache.videos.clone();
-        (*cache_id, videos)
-    }).collect();
-    returned
+    placement
 }
 
-fn cache_filling(cache_info: CacheInfo, videos: Vec<Video>) -> BTreeMap<i32, BTreeSet<i32>> {
-    let mut filled: BTreeMap<i32, FilledCache>
-        = (0..cache_info.count).map(|id| (id, FilledCache::new(cache_info.capacity))).collect();
+fn cache_filling(initial: &Placement, cache_info: CacheInfo, videos: Vec<Video>) -> Placement {
+    let mut placement = initial.clone();
 
     for video in videos {
         let mut current_cache: i32 = 0;
         let mut ok: bool = false;
         while current_cache < cache_info.count && !ok {
-            ok = filled.get_mut(&current_cache).unwrap().add_video(&video);
+            ok = placement.try_add(CacheId(current_cache), video.id);
             if !ok {
                 current_cache += 1;
             }
         }
     }
 
-    let returned: BTreeMap<i32, BTreeSet<i32>> = filled.iter().map(|(cache_id, cache)| {
-        let videos: BTreeSet<i32> = cache.videos.clone();
-        (*cache_id, videos)
-    }).collect();
-    returned
+    placement
 }
 
 pub enum GainMode {
@@ -101,341 +116,385 @@ pub enum GainMode {
     GainOverAudience
 }
 
-pub fn descent_gain(gain_mode: GainMode, ref cache_info: &CacheInfo, ref videos: &Vec<Video>,
-                    endpoints: Vec<Endpoint>, requests: Vec<Request>) -> BTreeMap<i32, Vec<(i32, i32)>> {
-    println!("Process the requests per video x endpoint");
-    let mut video_endpoint_to_request: BTreeMap<i32, BTreeMap<i32, i32>> = BTreeMap::new();
-
-    for request in requests {
-        if !video_endpoint_to_request.contains_key(&request.video_id) {
-            video_endpoint_to_request.insert(request.video_id, BTreeMap::new());
-        }
-        video_endpoint_to_request.get_mut(&request.video_id).unwrap().insert(request.endpoint_id, request.count);
-    }
+pub fn descent_gain(gain_mode: GainMode, context: &Context, cache_info: &CacheInfo, videos: &[Video],
+                    endpoints: Vec<Endpoint>, requests: Vec<Request>) -> BTreeMap<i64, Vec<(VideoId, CacheId)>> {
+    context.log(Verbosity::Verbose, || String::from("Build the dense instance"));
+    let dense = DenseInstance::new(cache_info, videos, &endpoints, &requests);
 
-    println!("Process the endpoints reacheable by a cache");
-    let mut datacenter_endpoint_to_latency: BTreeMap<i32, i32> = BTreeMap::new();
-    let mut cache_endpoint_to_latency: BTreeMap<i32, BTreeMap<i32, i32>> = BTreeMap::new();
+    context.log(Verbosity::Verbose, || String::from("Process the gain per video x endpoint"));
+    let mut tracker = context.tracker("gain", Some(videos.len() as u64));
 
-    for endpoint in endpoints {
-        for (cache_id, latency) in endpoint.cache_to_latency {
-            if cache_id >= 0 {
-                if !cache_endpoint_to_latency.contains_key(&cache_id) {
-                    cache_endpoint_to_latency.insert(cache_id, BTreeMap::new());
-                }
-                cache_endpoint_to_latency.get_mut(&cache_id).unwrap().insert(endpoint.id, latency);
-            } else {
-                datacenter_endpoint_to_latency.insert(endpoint.id, latency);
+    // Compute the gain for each cache x video
+    // Gain = sum per endpoint ( requests * (latency datacenter - latency cache) ), in 64 bits
+    // as it exceeds i32 on real inputs
+    let mut cache_gains: Vec<i64> = vec![0; cache_info.count as usize];
+    let mut cache_requests: Vec<i64> = vec![0; cache_info.count as usize];
+    let mut gains: BTreeMap<i64, Vec<(VideoId, CacheId)>> = BTreeMap::new();
+    for (index, video) in videos.iter().enumerate() {
+        tracker.update(index as u64, None);
+        for &(endpoint_id, requests) in dense.video_demand.row(video.id.index()) {
+            let datacenter_latency = dense.datacenter_latencies[endpoint_id.index()];
+            for &(cache_id, latency) in dense.endpoint_caches.row(endpoint_id.index()) {
+                cache_gains[cache_id.index()] += (datacenter_latency - latency) as i64 * requests;
+                cache_requests[cache_id.index()] += requests;
             }
         }
-    }
-
-    println!("Process the gain per video x endpoint");
-
-    // Compute the gain for each cache x video
-    // Gain = sum per endpoint ( requests * (latency datacenter - latency cache) )
-    let mut gains: BTreeMap<i32, Vec<(i32, i32)>> = BTreeMap::new();
-    for video in *videos {
-        println!("Video: {}", video.id);
-        let endpoint_to_request = video_endpoint_to_request.get(&video.id);
-        let endpoints: BTreeSet<i32> = match endpoint_to_request {
-            Some(endpoint_to_request) => endpoint_to_request.iter()
-                .map(|(&endpoint, _)| endpoint)
-                .collect(),
-            None => BTreeSet::new()
-        };
 
-        for cache_id in 0..cache_info.count {
-            let mut all_requests = 0;
-            let endpoints_latency = cache_endpoint_to_latency.get(&cache_id).unwrap();
-            let gain = endpoints_latency.iter()
-            .filter(|&(endpoint, _)| endpoints.contains(endpoint))
-            .fold(0, |gain, (endpoint, latency)| {
-                let requests = endpoint_to_request.unwrap().get(&endpoint).unwrap();
-                let datacenter_latency = datacenter_endpoint_to_latency.get(&endpoint).unwrap();
-                all_requests += *requests;
-                gain + (datacenter_latency - latency) * requests
-            });
-            let gain_over_audience = match all_requests {
+        for cache_id in cache_info.ids() {
+            let gain = mem::take(&mut cache_gains[cache_id.index()]);
+            let gain_over_audience = match mem::take(&mut cache_requests[cache_id.index()]) {
                 0 => 0,
-                _ => gain / all_requests
+                all_requests => gain / all_requests
             };
             let effective_gain = match gain_mode {
                 GainMode::PureGain => gain,
-                GainMode::GainOverCost => gain / video.size,
+                GainMode::GainOverCost => gain / max(video.size, 1) as i64,
                 GainMode::GainOverAudience => gain_over_audience
             };
-            if !gains.contains_key(&effective_gain) {
-                gains.insert(effective_gain, Vec::new());
-            }
-            gains.get_mut(&effective_gain).unwrap().push((video.id, cache_id));
+            gains.entry(effective_gain).or_default().push((video.id, cache_id));
         }
     }
 
-    println!("Done processing gain");
+    tracker.finish(videos.len() as u64, None);
+    context.record_candidates(gains.values().map(|candidates| candidates.len()).sum());
     gains
 }
 
-fn descent(gain_mode: GainMode, cache_info: CacheInfo, videos: Vec<Video>, endpoints: Vec<Endpoint>,
-           requests: Vec<Request>) -> BTreeMap<i32, BTreeSet<i32>> {
+fn descent(gain_mode: GainMode, context: &Context, initial: &Placement, cache_info: CacheInfo,
+           videos: Vec<Video>, endpoints: Vec<Endpoint>, requests: Vec<Request>) -> Placement {
 
-    let gains = descent_gain(gain_mode, &cache_info, &videos, endpoints, requests);
-    let mut filled: BTreeMap<i32, FilledCache>
-    = (0..cache_info.count).map(|id| (id, FilledCache::new(cache_info.capacity))).collect();
+    let gains = descent_gain(gain_mode, context, &cache_info, &videos, endpoints, requests);
+    let mut placement = initial.clone();
 
-    for (_, ref mapping) in gains.iter().rev() {
-        for &(video_id, cache_id) in *mapping {
-            filled.get_mut(&cache_id).unwrap().add_video(&videos[video_id as usize]);
+    let mut iteration: u64 = 0;
+    'fill: for mapping in gains.values().rev() {
+        for &(video_id, cache_id) in mapping {
+            if context.should_stop(iteration) {
+                break 'fill;
+            }
+            iteration += 1;
+            placement.try_add(cache_id, video_id);
         }
     }
 
 
-    let returned: BTreeMap<i32, BTreeSet<i32>> = filled.iter().map(|(cache_id, cache)| {
-        let videos: BTreeSet<i32> = cache.videos.clone();
-        (*cache_id, videos)
-    }).collect();
-    returned
+    placement
 }
 
-fn best_video(cache_info: CacheInfo, videos: Vec<Video>, endpoints: Vec<Endpoint>,
-              requests: Vec<Request>) -> BTreeMap<i32, BTreeSet<i32>> {
-    // First, compute endpoints for each video
-    let mut video_endpoint_to_request: BTreeMap<i32, BTreeMap<i32, i32>> = BTreeMap::new();
-    for video in &videos {
-        video_endpoint_to_request.insert(video.id, BTreeMap::new());
+fn best_video(context: &Context, initial: &Placement, cache_info: CacheInfo, videos: Vec<Video>,
+              endpoints: Vec<Endpoint>, requests: Vec<Request>) -> Placement {
+    let dense = DenseInstance::new(&cache_info, &videos, &endpoints, &requests);
+
+    // First, compute caches that are needed for each video
+    let mut video_to_caches: Vec<Vec<CacheId>> = Vec::with_capacity(videos.len());
+    let mut reached: Vec<bool> = vec![false; cache_info.count as usize];
+    let mut tracker = context.tracker("best video", Some(videos.len() as u64));
+    for (index, video) in videos.iter().enumerate() {
+        tracker.update(index as u64, None);
+        for &(endpoint_id, _) in dense.video_demand.row(video.id.index()) {
+            for &(cache_id, _) in dense.endpoint_caches.row(endpoint_id.index()) {
+                reached[cache_id.index()] = true;
+            }
+        }
+        video_to_caches.push(cache_info.ids().filter(|cache_id| mem::take(&mut reached[cache_id.index()])).collect());
     }
+    tracker.finish(videos.len() as u64, None);
+    context.record_candidates(video_to_caches.iter().map(|caches| caches.len()).sum());
 
-    for request in &requests {
-        video_endpoint_to_request.get_mut(&request.video_id).unwrap().insert(request.endpoint_id, request.count);
+    // Compute gain for each video
+    let mut gain_to_videos: BTreeMap<i64, Vec<VideoId>> = BTreeMap::new();
+    for video in &videos {
+        let gain: i64 = dense.video_demand.row(video.id.index()).iter().map(|&(_, requests)| requests).sum();
+        gain_to_videos.entry(gain).or_default().push(video.id);
     }
 
-    let mut datacenter_endpoint_to_latency: BTreeMap<i32, i32> = BTreeMap::new();
-    let mut cache_endpoint_to_latency: BTreeMap<i32, BTreeMap<i32, i32>> = BTreeMap::new();
+    // Fill caches
+    let mut placement = initial.clone();
 
-    for endpoint in endpoints {
-        for (cache_id, latency) in endpoint.cache_to_latency {
-            if cache_id >= 0 {
-                if !cache_endpoint_to_latency.contains_key(&cache_id) {
-                    cache_endpoint_to_latency.insert(cache_id, BTreeMap::new());
-                }
-                cache_endpoint_to_latency.get_mut(&cache_id).unwrap().insert(endpoint.id, latency);
-            } else {
-                datacenter_endpoint_to_latency.insert(endpoint.id, latency);
+    let mut iteration: u64 = 0;
+    'fill: for video_ids in gain_to_videos.values().rev() {
+        for video_id in video_ids {
+            if context.should_stop(iteration) {
+                break 'fill;
+            }
+            iteration += 1;
+            for cache_id in &video_to_caches[video_id.index()] {
+                placement.try_add(*cache_id, *video_id);
             }
         }
     }
 
-    // Then compute caches that are needed for each video
-    let mut video_to_caches: BTreeMap<i32, BTreeSet<i32>> = BTreeMap::new();
-    for video in &videos {
-        println!("Video: {}", video.id);
-        if !video_to_caches.contains_key(&video.id) {
-            video_to_caches.insert(video.id, BTreeSet::new());
-        }
+    placement
+}
 
-        let caches = video_to_caches.get_mut(&video.id).unwrap();
-        let endpoint_to_requests = video_endpoint_to_request.get(&video.id).unwrap();
-        for cache_id in 0..cache_info.count {
-            if cache_endpoint_to_latency.contains_key(&cache_id) {
-                for (endpoint, _) in cache_endpoint_to_latency.get(&cache_id).unwrap() {
-                    if endpoint_to_requests.contains_key(endpoint) {
-                        caches.insert(cache_id);
-                    }
-                }
+// Position of the candidate (video, cache) in the segmented gains
+fn candidate_index(cache_count: usize, video_id: VideoId, cache_id: CacheId) -> usize {
+    video_id.index() * cache_count + cache_id.index()
+}
+
+// Positions of the candidates of a video, in every cache
+fn video_candidates(cache_count: usize, video_id: VideoId) -> Range<usize> {
+    video_id.index() * cache_count..(video_id.index() + 1) * cache_count
+}
+
+// Returns for each (video, cache), at candidate_index, the latency saved per endpoint,
+// sorted by endpoint. None when no endpoint requesting the video reaches the cache.
+pub fn segmented_gain(context: &Context, cache_info: &CacheInfo, videos: &[Video],
+                      endpoints: Vec<Endpoint>, requests: Vec<Request>) -> Vec<Option<Vec<(EndpointId, i64)>>> {
+    context.log(Verbosity::Verbose, || String::from("Build the dense instance"));
+    let dense = DenseInstance::new(cache_info, videos, &endpoints, &requests);
+
+    context.log(Verbosity::Verbose, || String::from("Process the gain per video x endpoint"));
+    let mut tracker = context.tracker("gain", Some(videos.len() as u64));
+
+    let cache_count = cache_info.count as usize;
+    let mut gains: Vec<Option<Vec<(EndpointId, i64)>>> = Vec::with_capacity(videos.len() * cache_count);
+    let mut cache_gains: Vec<Vec<(EndpointId, i64)>> = vec![Vec::new(); cache_count];
+    for (index, video) in videos.iter().enumerate() {
+        tracker.update(index as u64, None);
+        for &(endpoint_id, requests) in dense.video_demand.row(video.id.index()) {
+            let datacenter_latency = dense.datacenter_latencies[endpoint_id.index()];
+            for &(cache_id, latency) in dense.endpoint_caches.row(endpoint_id.index()) {
+                cache_gains[cache_id.index()].push((endpoint_id, (datacenter_latency - latency) as i64 * requests));
             }
         }
+        // Copied into vectors of the exact size, the buffers being reused for the next video
+        gains.extend(cache_gains.iter_mut().map(|gain_per_endpoint| {
+            if gain_per_endpoint.is_empty() {
+                return None;
+            }
+            let exact = gain_per_endpoint.to_vec();
+            gain_per_endpoint.clear();
+            Some(exact)
+        }));
     }
 
-    // Compute gain for each video
-    let mut gain_to_videos: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
-    for video in &videos {
-        let gain = video_endpoint_to_request.values().fold(0, |gain, ref endpoint_to_request| {
-            gain + endpoint_to_request.values().fold(0, |gain, &request| gain + request)
-        });
-        if !gain_to_videos.contains_key(&gain) {
-            gain_to_videos.insert(gain, Vec::new());
-        }
-        gain_to_videos.get_mut(&gain).unwrap().push(video.id);
+    tracker.finish(videos.len() as u64, None);
+    context.record_candidates(gains.iter().filter(|gain_per_endpoint| gain_per_endpoint.is_some()).count());
+    gains
+}
+
+fn gain(video_id: VideoId, cache_id: CacheId, local_gains: &[(EndpointId, i64)], placement: &Placement) -> i64 {
+    if !placement.fits(cache_id, video_id) {
+        0
+    } else {
+        local_gains.iter().map(|&(_, gain)| gain).sum()
     }
+}
 
-    // Fill caches
-    let mut filled: BTreeMap<i32, FilledCache>
-    = (0..cache_info.count).map(|id| (id, FilledCache::new(cache_info.capacity))).collect();
+fn left_space(placement: &Placement) -> i64 {
+    placement.iter().map(|(cache_id, _)| placement.free_space(cache_id)).sum()
+}
 
-    for (_, ref video_ids) in gain_to_videos.iter().rev() {
-        for video_id in *video_ids {
-            for cache_id in video_to_caches.get(video_id).unwrap() {
-                filled.get_mut(&cache_id).unwrap().add_video(&videos[*video_id as usize]);
+// Removes the candidate of a placed video, and amends the other caches: putting
+// the video inside them is likely to yield a smaller gain
+fn amend_gains(gains: &mut [Option<Vec<(EndpointId, i64)>>], cache_count: usize, video_id: VideoId, cache_id: CacheId) {
+    if let Some(gain_per_endpoint) = gains[candidate_index(cache_count, video_id, cache_id)].take() {
+        for other_gain_per_endpoint in gains[video_candidates(cache_count, video_id)].iter_mut().flatten() {
+            for &(endpoint, gain) in &gain_per_endpoint {
+                if let Ok(index) = other_gain_per_endpoint.binary_search_by_key(&endpoint, |&(endpoint, _)| endpoint) {
+                    let local_gain = &mut other_gain_per_endpoint[index].1;
+                    *local_gain = max(*local_gain - gain, 0);
+                }
             }
         }
     }
-
-    let returned: BTreeMap<i32, BTreeSet<i32>> = filled.iter().map(|(cache_id, cache)| {
-        let videos: BTreeSet<i32> = cache.videos.clone();
-        (*cache_id, videos)
-    }).collect();
-    returned
 }
 
-// Returns for (video, cache) a map of endpoint -> saved
-pub fn segmented_gain(ref cache_info: &CacheInfo, ref videos: &Vec<Video>,
-                      endpoints: Vec<Endpoint>, requests: Vec<Request>) -> BTreeMap<(i32, i32), BTreeMap<i32, i32>> {
-    println!("Process the requests per video x endpoint");
-    let mut video_endpoint_to_request: BTreeMap<i32, BTreeMap<i32, i32>> = BTreeMap::new();
+fn descent_amend(context: &Context, initial: &Placement, cache_info: CacheInfo,
+                 videos: Vec<Video>, endpoints: Vec<Endpoint>, requests: Vec<Request>) -> Placement {
 
-    for request in requests {
-        if !video_endpoint_to_request.contains_key(&request.video_id) {
-            video_endpoint_to_request.insert(request.video_id, BTreeMap::new());
+    let cache_count = cache_info.count as usize;
+    let mut gains = segmented_gain(context, &cache_info, &videos, endpoints, requests);
+    let mut placement = initial.clone();
+    for (cache_id, video_ids) in initial.iter() {
+        for &video_id in video_ids {
+            amend_gains(&mut gains, cache_count, video_id, cache_id);
         }
-        video_endpoint_to_request.get_mut(&request.video_id).unwrap().insert(request.endpoint_id, request.count);
     }
 
-    println!("Process the endpoints reacheable by a cache");
-    let mut datacenter_endpoint_to_latency: BTreeMap<i32, i32> = BTreeMap::new();
-    let mut cache_endpoint_to_latency: BTreeMap<i32, BTreeMap<i32, i32>> = BTreeMap::new();
-
-    for endpoint in endpoints {
-        for (cache_id, latency) in endpoint.cache_to_latency {
-            if cache_id >= 0 {
-                if !cache_endpoint_to_latency.contains_key(&cache_id) {
-                    cache_endpoint_to_latency.insert(cache_id, BTreeMap::new());
-                }
-                cache_endpoint_to_latency.get_mut(&cache_id).unwrap().insert(endpoint.id, latency);
-            } else {
-                datacenter_endpoint_to_latency.insert(endpoint.id, latency);
+    // Candidates ranked by gain, then by decreasing ids, so that the best one is
+    // always the last one and equal gains go to the smallest video and cache ids
+    let mut computed_gains: Vec<i64> = vec![0; gains.len()];
+    let mut ranked: BTreeSet<(i64, Reverse<VideoId>, Reverse<CacheId>)> = BTreeSet::new();
+    for video in &videos {
+        for cache_id in cache_info.ids() {
+            let index = candidate_index(cache_count, video.id, cache_id);
+            if let Some(ref local_gains) = gains[index] {
+                let gain = gain(video.id, cache_id, local_gains, &placement);
+                computed_gains[index] = gain;
+                ranked.insert((gain, Reverse(video.id), Reverse(cache_id)));
             }
         }
     }
 
-    println!("Process the gain per video x endpoint");
-
-    let mut gains: BTreeMap<(i32, i32), BTreeMap<i32, i32>> = BTreeMap::new();
-    for video in *videos {
-        println!("Video: {}", video.id);
-        let endpoint_to_request = video_endpoint_to_request.get(&video.id);
-        let endpoints: BTreeSet<i32> = match endpoint_to_request {
-            Some(endpoint_to_request) => endpoint_to_request.iter()
-                .map(|(&endpoint, _)| endpoint)
-                .collect(),
-            None => BTreeSet::new()
+    let total = left_space(&placement);
+    let mut left = left_space(&placement);
+    let mut iteration: u64 = 0;
+    let mut tracker = context.tracker("descent amend", Some(total as u64));
+    while left > 0 && !ranked.is_empty() && !context.should_stop(iteration) {
+        iteration += 1;
+        tracker.update((total - left) as u64, None);
+
+        // Build the best gain
+        let (current_gain, current_video_id, current_cache_id) = match ranked.iter().next_back() {
+            Some(&(gain, Reverse(video_id), Reverse(cache_id))) if gain > 0 => (gain, video_id, cache_id),
+            _ => break
         };
 
-        for cache_id in 0..cache_info.count {
-            gains.insert((video.id, cache_id), BTreeMap::new());
-            let mut gain_map = gains.get_mut(&(video.id, cache_id)).unwrap();
-
-            let endpoints_latency = cache_endpoint_to_latency.get(&cache_id).unwrap();
-            let endpoints = endpoints_latency.iter().filter(|&(endpoint, _)| endpoints.contains(endpoint));
-            for (endpoint, latency) in endpoints {
-                let requests = endpoint_to_request.unwrap().get(&endpoint).unwrap();
-                let datacenter_latency = datacenter_endpoint_to_latency.get(&endpoint).unwrap();
-                let gain = (datacenter_latency - latency) * requests;
-                gain_map.insert(*endpoint, gain);
+        context.log(Verbosity::Verbose, || {
+            format!("Best gain: {} for video {} and cache {}", current_gain, current_video_id, current_cache_id)
+        });
+        placement.try_add(current_cache_id, current_video_id);
+        amend_gains(&mut gains, cache_count, current_video_id, current_cache_id);
+        ranked.remove(&(current_gain, Reverse(current_video_id), Reverse(current_cache_id)));
+
+        for cache_id in cache_info.ids() {
+            let index = candidate_index(cache_count, current_video_id, cache_id);
+            if let Some(ref other_gain_per_endpoint) = gains[index] {
+                let new_gain = gain(current_video_id, cache_id, other_gain_per_endpoint, &placement);
+                ranked.remove(&(computed_gains[index], Reverse(current_video_id), Reverse(cache_id)));
+                ranked.insert((new_gain, Reverse(current_video_id), Reverse(cache_id)));
+                computed_gains[index] = new_gain;
             }
         }
-    }
 
-    println!("Done processing gain");
-    gains
-}
+        // Videos that do not fit anymore in the filled cache yield no gain
+        let free_space = placement.free_space(current_cache_id);
+        for video in videos.iter().filter(|video| video.size as i64 > free_space) {
+            let index = candidate_index(cache_count, video.id, current_cache_id);
+            if gains[index].is_some() {
+                ranked.remove(&(computed_gains[index], Reverse(video.id), Reverse(current_cache_id)));
+                ranked.insert((0, Reverse(video.id), Reverse(current_cache_id)));
+                computed_gains[index] = 0;
+            }
+        }
 
-fn gain(video_id: i32, cache_id: i32, videos: &Vec<Video>,
-        gains: &BTreeMap<(i32, i32), BTreeMap<i32, i32>>, filled: &BTreeMap<i32, FilledCache>) -> i32 {
-    if videos[video_id as usize].size > filled.get(&cache_id).unwrap().remaining_capacity {
-        0
-    } else {
-        gains.get(&(video_id, cache_id)).unwrap().values().fold(0, |gain, value| gain + value)
+        left = left_space(&placement);
     }
-}
+    tracker.finish((total - left) as u64, None);
 
-fn gain_simple(video_id: i32, cache_id: i32, videos: &Vec<Video>,
-               local_gains: &BTreeMap<i32, i32>, filled: &BTreeMap<i32, FilledCache>) -> i32 {
-    if videos[video_id as usize].size > filled.get(&cache_id).unwrap().remaining_capacity {
-        0
-    } else {
-        local_gains.values().fold(0, |gain, value| gain + value)
-    }
+    placement
 }
 
-fn left_space(filled: &BTreeMap<i32, FilledCache>) -> i32 {
-    filled.values().fold(0, |space, ref cache| space + cache.remaining_capacity)
+// Demands served by a cache in descent throughput, each (endpoint, video) demand
+// being served as a whole by a single cache
+struct Reservations {
+    // Per position in video_demand, the serving cache and its latency
+    served: Vec<Option<(CacheId, i32)>>,
+    // Requests each cache can still serve
+    throughputs: Vec<i64>
 }
 
-fn descent_amend(cache_info: CacheInfo, videos: Vec<Video>, endpoints: Vec<Endpoint>,
-                 requests: Vec<Request>) -> BTreeMap<i32, BTreeSet<i32>> {
-
-    let mut gains = segmented_gain(&cache_info, &videos, endpoints, requests);
-    let mut filled: BTreeMap<i32, FilledCache>
-    = (0..cache_info.count).map(|id| (id, FilledCache::new(cache_info.capacity))).collect();
-
-    let mut computed_gains: BTreeMap<(i32, i32), i32> = BTreeMap::new();
-    for (&(video_id, cache_id), _) in &gains {
-        computed_gains.insert((video_id, cache_id), gain(video_id, cache_id, &videos, &gains, &filled));
+impl Reservations {
+    fn new(dense: &DenseInstance, cache_info: &CacheInfo) -> Reservations {
+        Reservations {
+            served: vec![None; dense.video_demand.items().len()],
+            throughputs: cache_info.caches.iter().map(|cache| cache.throughput.unwrap_or(i64::MAX)).collect()
+        }
     }
 
-    let total = left_space(&filled);
-    let mut left = left_space(&filled);
-    while left > 0 && gains.len() > 0 {
-        println!("{} / {}", left, total);
-
-        // Build the best gain
-        let mut current_cache_id = -1;
-        let mut current_video_id = -1;
-        let mut current_gain = 0;
-
-        for (&(video_id, cache_id), &local_gain) in &computed_gains {
-            if local_gain > current_gain {
-                current_video_id = video_id;
-                current_cache_id = cache_id;
-                current_gain = local_gain;
+    // Demands of the video the cache would serve faster, the largest savings per
+    // request first, within the throughput left. Returns their total saving and
+    // their positions along with the latency of the cache.
+    fn moves(&self, dense: &DenseInstance, video_id: VideoId, cache_id: CacheId) -> (i64, Vec<(usize, i32)>) {
+        let offset = dense.video_demand.offset(video_id.index());
+        let mut candidates: Vec<(i32, usize, i32, i64)> = Vec::new();
+        for (index, &(endpoint_id, count)) in dense.video_demand.row(video_id.index()).iter().enumerate() {
+            let caches = dense.endpoint_caches.row(endpoint_id.index());
+            if let Ok(link) = caches.binary_search_by_key(&cache_id, |&(cache_id, _)| cache_id) {
+                let latency = caches[link].1;
+                let current = self.served[offset + index]
+                    .map_or(dense.datacenter_latencies[endpoint_id.index()], |(_, latency)| latency);
+                if latency < current {
+                    candidates.push((current - latency, offset + index, latency, count));
+                }
             }
         }
+        candidates.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        let mut left = self.throughputs[cache_id.index()];
+        let mut saving: i64 = 0;
+        let mut moves: Vec<(usize, i32)> = Vec::new();
+        for (improvement, position, latency, count) in candidates {
+            if count <= left {
+                left -= count;
+                saving += improvement as i64 * count;
+                moves.push((position, latency));
+            }
+        }
+        (saving, moves)
+    }
 
-        println!("Best gain: {} for video {} and cache {}", current_cache_id, current_video_id, current_cache_id);
-        filled.get_mut(&current_cache_id).unwrap().add_video(&videos[current_video_id as usize]);
-        let gain_per_endpoint: BTreeMap<i32, i32> = gains.get(&(current_video_id, current_cache_id)).unwrap().clone();
-        gains.remove(&(current_video_id, current_cache_id));
-        computed_gains.remove(&(current_video_id, current_cache_id));
-
-        // Amend all other caches: putting the video inside them is likely to yield a smaller gain
-        for (&(video_id, cache_id), mut other_gain_per_endpoint) in gains.iter().filter(|&(&(video_id, _), _)| video_id == current_video_id) {
-            let other_endpoints: BTreeSet<&i32> = gain_per_endpoint.keys().clone().collect();
-            for endpoint_id in other_endpoints.iter().filter(|&endpoint_id| other_endpoints.contains(endpoint_id)) {
-                
+    // Serves the demands by the cache, releasing the throughput of their previous one
+    fn apply(&mut self, dense: &DenseInstance, cache_id: CacheId, moves: &[(usize, i32)]) {
+        for &(position, latency) in moves {
+            let count = dense.video_demand.items()[position].1;
+            if let Some((previous_id, _)) = self.served[position] {
+                self.throughputs[previous_id.index()] += count;
             }
+            self.throughputs[cache_id.index()] -= count;
+            self.served[position] = Some((cache_id, latency));
         }
+    }
+}
 
+// Greedy aware of the throughput of the caches: a video is worth what its cache
+// can still serve faster. Placing a video changes the gain of other candidates,
+// which are re-evaluated when they come first.
+fn descent_throughput(context: &Context, initial: &Placement, cache_info: CacheInfo, videos: Vec<Video>,
+                      endpoints: Vec<Endpoint>, requests: Vec<Request>) -> Placement {
+    let dense = DenseInstance::new(&cache_info, &videos, &endpoints, &requests);
+    let mut reservations = Reservations::new(&dense, &cache_info);
+    let mut placement = initial.clone();
+    for (cache_id, video_ids) in initial.iter() {
+        for &video_id in video_ids {
+            let (_, moves) = reservations.moves(&dense, video_id, cache_id);
+            reservations.apply(&dense, cache_id, &moves);
+        }
+    }
 
-        /*
-        gains.iter_mut()
-            .filter(|&(&(video_id, _), _)| { video_id == current_video_id })
-            .map(|(&(video_id, cache_id), mut other_gain_per_endpoint)| {
-                other_gain_per_endpoint.iter_mut()
-                    .filter(|&(endpoint, _)| { other_endpoints.has(&endpoint) })
-                    .map(|&(endpoint, gain)| {
-
-                    }).count();
-
-                for (endpoint, gain) in &gain_per_endpoint {
-                    if other_gain_per_endpoint.contains_key(&endpoint) {
-                        let local_gain = other_gain_per_endpoint.get_mut(&endpoint).unwrap();
-                        let previous_local_gain: i32 = *local_gain;
-                        let new_local_gain = max(previous_local_gain - gain, 0);
-                        println!("New local gain {} {} {}", new_local_gain, previous_local_gain, gain);
-                        // other_gain_per_endpoint.insert(*endpoint, new_local_gain);
-                        //local_gain = new_local_gain as &mut i32;
-                    }
+    let mut candidates: BinaryHeap<(i64, VideoId, CacheId)> = BinaryHeap::new();
+    let mut reached: Vec<bool> = vec![false; cache_info.count as usize];
+    let mut tracker = context.tracker("gain", Some(videos.len() as u64));
+    for (index, video) in videos.iter().enumerate() {
+        tracker.update(index as u64, None);
+        for &(endpoint_id, _) in dense.video_demand.row(video.id.index()) {
+            for &(cache_id, _) in dense.endpoint_caches.row(endpoint_id.index()) {
+                reached[cache_id.index()] = true;
+            }
+        }
+        for cache_id in cache_info.ids().filter(|cache_id| mem::take(&mut reached[cache_id.index()])) {
+            if placement.fits(cache_id, video.id) {
+                let (saving, _) = reservations.moves(&dense, video.id, cache_id);
+                if saving > 0 {
+                    candidates.push((saving, video.id, cache_id));
                 }
-                computed_gains.insert((video_id, cache_id), gain_simple(video_id, cache_id, &videos, &other_gain_per_endpoint, &filled));
-            }).count();
-        */
+            }
+        }
+    }
+    tracker.finish(videos.len() as u64, None);
+    context.record_candidates(candidates.len());
 
-        left = left_space(&filled);
+    let mut iteration: u64 = 0;
+    while let Some((saving, video_id, cache_id)) = candidates.pop() {
+        if context.should_stop(iteration) {
+            break;
+        }
+        iteration += 1;
+        if !placement.fits(cache_id, video_id) {
+            continue;
+        }
+        let (current_saving, moves) = reservations.moves(&dense, video_id, cache_id);
+        if current_saving < saving {
+            if current_saving > 0 {
+                candidates.push((current_saving, video_id, cache_id));
+            }
+            continue;
+        }
+        placement.try_add(cache_id, video_id);
+        reservations.apply(&dense, cache_id, &moves);
     }
 
-    let returned: BTreeMap<i32, BTreeSet<i32>> = filled.iter().map(|(cache_id, cache)| {
-        let videos: BTreeSet<i32> = cache.videos.clone();
-        (*cache_id, videos)
-    }).collect();
-    returned
-}
\ No newline at end of file
+    placement
+}
@@ -0,0 +1,49 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+// Outcome of one input of a batch: its best score, or why it failed
+pub struct BatchResult {
+    pub file: String,
+    pub result: Result<i64, String>
+}
+
+// Runs the task on every input with at most jobs worker threads. Workers take
+// the next input as soon as they are done, and results keep the input order.
+pub fn run_batch<T, F>(inputs: &[String], jobs: usize, task: F) -> Vec<T>
+    where T: Send, F: Fn(&str) -> T + Sync {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<T>>> = Mutex::new(inputs.iter().map(|_| None).collect());
+    let workers = jobs.max(1).min(inputs.len());
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= inputs.len() {
+                    break;
+                }
+                let result = task(&inputs[index]);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    results.into_inner().unwrap().into_iter().map(|result| result.unwrap()).collect()
+}
+
+// Prefixes every line with the file name, so that concurrent outputs can be told apart
+pub fn prefix_lines(file: &str, text: &str) -> String {
+    text.lines().fold(String::new(), |result, line| result + &format!("{}: {}\n", file, line))
+}
+
+pub fn format_summary(results: &[BatchResult]) -> String {
+    let header = format!("{:<40} {:>12}\n", "file", "score");
+    let table = results.iter().fold(header, |result, batch_result| {
+        match batch_result.result {
+            Ok(score) => result + &format!("{:<40} {:>12}\n", batch_result.file, score),
+            Err(ref err) => result + &format!("{:<40} {:>12} ({})\n", batch_result.file, "failed", err)
+        }
+    });
+    let succeeded: Vec<i64> = results.iter().filter_map(|batch_result| batch_result.result.clone().ok()).collect();
+    table + &format!("{} succeeded, {} failed, total score {}\n", succeeded.len(),
+                     results.len() - succeeded.len(), succeeded.iter().sum::<i64>())
+}
@@ -0,0 +1,60 @@
+use portfolio::Run;
+use std::fs;
+
+pub struct BenchResult {
+    pub file: String,
+    pub runs: Vec<Run>
+}
+
+// The .in files of a directory, sorted by name
+pub fn list_inputs(dir: &str) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(dir).map_err(|err| format!("{}: {}", dir, err))?;
+    let mut inputs: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|extension| extension == "in"))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    inputs.sort();
+    Ok(inputs)
+}
+
+// One file x solver matrix for the scores, one for the wall times and one for
+// the peak candidate table sizes, each with a total row
+pub fn format_matrix(results: &[BenchResult]) -> String {
+    let solvers: Vec<String> = results.first()
+        .map(|result| result.runs.iter().map(|run| run.name()).collect())
+        .unwrap_or_default();
+    let score = |run: &Run| if run.valid { run.score as u128 } else { 0 };
+    let time = |run: &Run| run.duration.as_millis();
+    let peak = |run: &Run| run.peak_candidates as u128;
+
+    format_table("Score", &solvers, results, &score) + "\n"
+        + &format_table("Time (ms)", &solvers, results, &time) + "\n"
+        + &format_table("Peak candidates", &solvers, results, &peak)
+}
+
+fn format_table(title: &str, solvers: &[String], results: &[BenchResult], value: &dyn Fn(&Run) -> u128) -> String {
+    let header = solvers.iter().fold(format!("{:<32}", title), |result, solver| {
+        result + &format!(" {:>20}", solver)
+    }) + "\n";
+
+    let mut totals: Vec<u128> = vec![0; solvers.len()];
+    let rows = results.iter().fold(header, |result, bench_result| {
+        let name = bench_result.file.rsplit('/').next().unwrap_or(&bench_result.file);
+        let row = solvers.iter().enumerate().fold(format!("{:<32}", name), |row, (index, solver)| {
+            match bench_result.runs.iter().find(|run| &run.name() == solver) {
+                Some(run) => {
+                    totals[index] += value(run);
+                    row + &format!(" {:>20}", value(run))
+                }
+                None => row + &format!(" {:>20}", "-")
+            }
+        });
+        result + &row + "\n"
+    });
+
+    totals.iter().fold(rows + &format!("{:<32}", "total"), |result, total| {
+        result + &format!(" {:>20}", total)
+    }) + "\n"
+}
@@ -0,0 +1,451 @@
+use algo::Mode;
+use progress::Verbosity;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: videos [solve] [OPTIONS] INPUT...
+       videos score [--constraints FILE] INPUT SUBMISSION
+       videos stats [--key-value] INPUT...
+       videos bench [OPTIONS] [DIR|INPUT]...
+       videos package [-d DIR]... [-o DIR] [--source DIR] [DIR|INPUT]...
+       videos plan [OPTIONS] [--migration-cost N] INPUT
+
+Places videos in caches for each INPUT and writes the best placement found.
+An INPUT, SUBMISSION or FILE of - is read from stdin. The placement of an input
+read from stdin is written to stdout, along with -o -, and everything else then
+goes to stderr.
+
+Commands:
+  solve                 Solve the inputs (default)
+  score                 Check and score a submission for an input
+  stats                 Describe the inputs, or print key=value lines with --key-value
+  bench                 Run the solvers on every .in file of the directories and
+                        inputs [default: resources] and compare scores, times
+                        and peak candidate table sizes
+  package               Copy the best valid placement of every input [default:
+                        resources] found in the output directories (-d, may be
+                        repeated) to DIR/<input stem>.out, along with a zip of
+                        the sources [default: -o submission, --source the
+                        directory of this crate]
+  plan                  Solve each time slot of the input with one solver
+                        [default: descent-amend] and write the placement of
+                        slot N to DIR/<input name>.N.out, paying the migration
+                        cost [default: 0] per unit of video size copied into
+                        the caches between consecutive slots. Takes the options
+                        of solve but -o, -j and --warm-start
+  help                  Print this help
+
+Options:
+  -c, --config FILE     Read solver settings from FILE, see Configuration below.
+                        Options given on the command line take precedence
+  -o, --output FILE     Write the placement to FILE (single input only)
+  -d, --output-dir DIR  Write placements to DIR/<input name>.out [default: output]
+  -s, --solver NAME     Run only this solver, may be repeated [default: all]
+      --seed N          Seed for randomized steps [default: 0]
+  -t, --time-limit SEC  Time budget of each solver run, in seconds
+  -j, --jobs N          Solve up to N inputs at once [default: one per CPU]
+      --no-local-search Do not follow each solver by a local search
+      --warm-start FILE Start from this submission (single input only)
+      --constraints FILE
+                        Keep the videos pinned to a cache, never store the
+                        forbidden ones and keep the number of copies of each
+                        video within its limits, see Constraints below
+      --no-replication-report
+                        Do not rerun the best strategy without the replication
+                        limits to report what they cost
+  -q, --quiet           Only print results
+  -v, --verbose         Print solver details
+  -h, --help            Print this help
+
+Configuration:
+  A configuration file has one key = value per line, # starting a comment.
+  Keys in [general], or before the first section, apply to every input:
+    solvers = NAME, ...         Solvers to run
+    seed = N                    Seed for randomized steps
+    time_limit = SEC            Time budget of each solver run
+    max_iterations = N          Iteration budget of each solver run
+    local_search = true|false   Follow each solver by a local search
+    jobs = N                    Inputs solved at once ([general] only)
+  A [NAME] section, NAME being a solver or local-search, sets the time_limit
+  and max_iterations of that solver only. [input FILE] and [input FILE NAME]
+  sections override these settings for an input, matched by path or file name.
+
+Constraints:
+  A constraints file has one constraint per line, # starting a comment:
+    pin VIDEO CACHE             Always store VIDEO in CACHE
+    forbid VIDEO CACHE          Never store VIDEO in CACHE
+    replicas VIDEO MIN MAX      Store VIDEO in MIN to MAX caches
+  A * VIDEO in replicas sets the limits of every video without limits of its
+  own, and a * MAX means no maximum.
+
+Solvers: dummy, cache-spreading, cache-filling, descent, descent-cost,
+descent-audience, best-video, descent-amend, descent-throughput
+
+Exit status is 0 on success, 1 if an input failed or a submission is invalid
+and 2 on usage or configuration errors.
+";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Solve(SolveOptions),
+    Score(ScoreOptions),
+    Stats(StatsOptions),
+    Bench(SolveOptions),
+    Package(PackageOptions),
+    Plan(PlanOptions),
+    Help
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ScoreOptions {
+    pub input: String,
+    pub submission: String,
+    pub constraints: Option<String>
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StatsOptions {
+    pub inputs: Vec<String>,
+    pub key_value: bool
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PackageOptions {
+    pub inputs: Vec<String>,
+    // Where to look for placements
+    pub output_dirs: Vec<String>,
+    // Where the submission is written
+    pub destination: String,
+    // Root of the sources to zip
+    pub source: String
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PlanOptions {
+    // A single input and a single solver
+    pub solve: SolveOptions,
+    pub migration_cost: i64
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolveOptions {
+    pub inputs: Vec<String>,
+    pub output: Option<String>,
+    pub output_dir: String,
+    pub modes: Vec<Mode>,
+    pub seed: u64,
+    pub time_limit: Option<Duration>,
+    // None to use one worker per CPU
+    pub jobs: Option<usize>,
+    pub local_search: bool,
+    pub warm_start: Option<String>,
+    pub constraints: Option<String>,
+    // Whether to rerun the best strategy without the replication limits
+    pub replication_report: bool,
+    pub verbosity: Verbosity,
+    pub config: Option<String>,
+    // Configuration keys set on the command line, which the configuration file
+    // does not override
+    pub command_line: BTreeSet<&'static str>
+}
+
+impl SolveOptions {
+    pub fn new() -> SolveOptions {
+        SolveOptions {
+            inputs: Vec::new(),
+            output: None,
+            output_dir: String::from("output"),
+            modes: Vec::new(),
+            seed: 0,
+            time_limit: None,
+            jobs: None,
+            local_search: true,
+            warm_start: None,
+            constraints: None,
+            replication_report: true,
+            verbosity: Verbosity::Normal,
+            config: None,
+            command_line: BTreeSet::new()
+        }
+    }
+
+    // Where the placement of an input is written, - for stdout
+    pub fn output_for(&self, input: &str) -> String {
+        match self.output {
+            Some(ref output) => output.clone(),
+            None if input == "-" => String::from("-"),
+            None => output_path(&self.output_dir, input)
+        }
+    }
+}
+
+// Where solve writes the placement of an input in an output directory
+pub fn output_path(output_dir: &str, input: &str) -> String {
+    let name = input.rsplit('/').next().unwrap_or(input);
+    format!("{}/{}.out", output_dir.trim_end_matches('/'), name)
+}
+
+// Where plan writes the placement of a time slot
+pub fn slot_output_path(output_dir: &str, input: &str, slot: usize) -> String {
+    let name = input.rsplit('/').next().unwrap_or(input);
+    format!("{}/{}.{}.out", output_dir.trim_end_matches('/'), name, slot)
+}
+
+impl Default for SolveOptions {
+    fn default() -> SolveOptions {
+        SolveOptions::new()
+    }
+}
+
+// Parses the arguments, without the program name
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter().peekable();
+    match args.peek().map(|arg| arg.as_str()) {
+        Some("help") => return Ok(Command::Help),
+        Some("score") => {
+            args.next();
+            return parse_score_args(args.cloned().collect());
+        }
+        Some("stats") => {
+            args.next();
+            return parse_stats_args(args.cloned().collect());
+        }
+        Some("bench") => {
+            args.next();
+            let mut options = match parse_solve_args(args)? {
+                Some(options) => options,
+                None => return Ok(Command::Help)
+            };
+            if options.output.is_some() || options.warm_start.is_some() {
+                return Err(String::from("bench does not write placements"));
+            }
+            if options.jobs.is_some() {
+                return Err(String::from("bench solves one input at a time to keep times comparable"));
+            }
+            if options.inputs.is_empty() {
+                options.inputs.push(String::from("resources"));
+            }
+            return Ok(Command::Bench(options));
+        }
+        Some("package") => {
+            args.next();
+            return parse_package_args(args);
+        }
+        Some("plan") => {
+            args.next();
+            return parse_plan_args(args);
+        }
+        Some("solve") => {
+            args.next();
+        }
+        _ => ()
+    }
+
+    let options = match parse_solve_args(args)? {
+        Some(options) => options,
+        None => return Ok(Command::Help)
+    };
+    if options.inputs.is_empty() {
+        return Err(String::from("No input given"));
+    }
+    if options.inputs.len() > 1 && options.output.is_some() {
+        return Err(String::from("--output can only be used with a single input"));
+    }
+    if options.inputs.len() > 1 && options.warm_start.is_some() {
+        return Err(String::from("--warm-start can only be used with a single input"));
+    }
+    if options.inputs.len() > 1 && options.inputs.iter().any(|input| input == "-") {
+        return Err(String::from("- can only be used as a single input"));
+    }
+    if options.inputs[0] == "-" && options.warm_start.as_ref().is_some_and(|warm_start| warm_start == "-") {
+        return Err(String::from("stdin can only be read once"));
+    }
+    if options.inputs[0] == "-" && options.constraints.as_ref().is_some_and(|constraints| constraints == "-") {
+        return Err(String::from("stdin can only be read once"));
+    }
+    Ok(Command::Solve(options))
+}
+
+// None when help is requested
+fn parse_solve_args<'a, I: Iterator<Item = &'a String>>(mut args: I) -> Result<Option<SolveOptions>, String> {
+    let mut options = SolveOptions::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => options.output = Some(value(arg, args.next())?.clone()),
+            "-d" | "--output-dir" => options.output_dir = value(arg, args.next())?.clone(),
+            "-s" | "--solver" => {
+                let name = value(arg, args.next())?;
+                let mode = Mode::from_name(name).ok_or(format!("Unknown solver {}", name))?;
+                options.modes.push(mode);
+                options.command_line.insert("solvers");
+            }
+            "--seed" => {
+                options.seed = parse_number(arg, value(arg, args.next())?)?;
+                options.command_line.insert("seed");
+            }
+            "-t" | "--time-limit" => {
+                let seconds: f64 = parse_number(arg, value(arg, args.next())?)?;
+                if !seconds.is_finite() || seconds < 0. {
+                    return Err(format!("Invalid value for {}: {}", arg, seconds));
+                }
+                options.time_limit = Some(Duration::from_secs_f64(seconds));
+                options.command_line.insert("time_limit");
+            }
+            "-j" | "--jobs" => {
+                let jobs: usize = parse_number(arg, value(arg, args.next())?)?;
+                if jobs == 0 {
+                    return Err(format!("Invalid value for {}: {}", arg, jobs));
+                }
+                options.jobs = Some(jobs);
+                options.command_line.insert("jobs");
+            }
+            "--no-local-search" => {
+                options.local_search = false;
+                options.command_line.insert("local_search");
+            }
+            "-c" | "--config" => options.config = Some(value(arg, args.next())?.clone()),
+            "--warm-start" => options.warm_start = Some(value(arg, args.next())?.clone()),
+            "--constraints" => options.constraints = Some(value(arg, args.next())?.clone()),
+            "--no-replication-report" => options.replication_report = false,
+            "-q" | "--quiet" => options.verbosity = Verbosity::Silent,
+            "-v" | "--verbose" => options.verbosity = Verbosity::Verbose,
+            _ if arg.starts_with('-') && arg.len() > 1 => return Err(format!("Unknown option {}", arg)),
+            _ => options.inputs.push(arg.clone())
+        }
+    }
+
+    if options.modes.is_empty() {
+        options.modes = Mode::all();
+    }
+    Ok(Some(options))
+}
+
+fn parse_score_args(args: Vec<String>) -> Result<Command, String> {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        return Ok(Command::Help);
+    }
+    let mut files: Vec<String> = Vec::new();
+    let mut constraints: Option<String> = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--constraints" => constraints = Some(value(arg, args.next())?.clone()),
+            _ if arg.starts_with('-') && arg.len() > 1 => return Err(format!("Unknown option {}", arg)),
+            _ => files.push(arg.clone())
+        }
+    }
+    if files.iter().chain(&constraints).filter(|file| *file == "-").count() > 1 {
+        return Err(String::from("stdin can only be read once"));
+    }
+    match files.len() {
+        2 => Ok(Command::Score(ScoreOptions {
+            input: files[0].clone(),
+            submission: files[1].clone(),
+            constraints
+        })),
+        _ => Err(String::from("score expects an input and a submission"))
+    }
+}
+
+fn parse_stats_args(args: Vec<String>) -> Result<Command, String> {
+    let mut options = StatsOptions {
+        inputs: Vec::new(),
+        key_value: false
+    };
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--key-value" => options.key_value = true,
+            _ if arg.starts_with('-') && arg.len() > 1 => return Err(format!("Unknown option {}", arg)),
+            _ => options.inputs.push(arg)
+        }
+    }
+
+    if options.inputs.is_empty() {
+        return Err(String::from("No input given"));
+    }
+    if options.inputs.iter().filter(|input| *input == "-").count() > 1 {
+        return Err(String::from("stdin can only be read once"));
+    }
+    Ok(Command::Stats(options))
+}
+
+fn parse_package_args<'a, I: Iterator<Item = &'a String>>(mut args: I) -> Result<Command, String> {
+    let mut options = PackageOptions {
+        inputs: Vec::new(),
+        output_dirs: Vec::new(),
+        destination: String::from("submission"),
+        source: String::from(env!("CARGO_MANIFEST_DIR"))
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-d" | "--output-dir" => options.output_dirs.push(value(arg, args.next())?.clone()),
+            "-o" | "--output" => options.destination = value(arg, args.next())?.clone(),
+            "--source" => options.source = value(arg, args.next())?.clone(),
+            _ if arg.starts_with('-') && arg.len() > 1 => return Err(format!("Unknown option {}", arg)),
+            _ => options.inputs.push(arg.clone())
+        }
+    }
+
+    if options.inputs.is_empty() {
+        options.inputs.push(String::from("resources"));
+    }
+    if options.output_dirs.is_empty() {
+        options.output_dirs.push(String::from("output"));
+    }
+    Ok(Command::Package(options))
+}
+
+// The options of solve, with a single solver, plus the migration cost
+fn parse_plan_args<'a, I: Iterator<Item = &'a String>>(mut args: I) -> Result<Command, String> {
+    let mut migration_cost: i64 = 0;
+    let mut solve_args: Vec<String> = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--migration-cost" {
+            migration_cost = parse_number(arg, value(arg, args.next())?)?;
+            if migration_cost < 0 {
+                return Err(format!("Invalid value for {}: {}", arg, migration_cost));
+            }
+        } else {
+            solve_args.push(arg.clone());
+        }
+    }
+
+    let mut options = match parse_solve_args(solve_args.iter())? {
+        Some(options) => options,
+        None => return Ok(Command::Help)
+    };
+    if !options.command_line.contains("solvers") {
+        options.modes = vec![Mode::DescentAmend];
+    }
+    if options.modes.len() > 1 {
+        return Err(String::from("plan runs a single solver"));
+    }
+    if options.output.is_some() {
+        return Err(String::from("plan writes one placement per slot to the output directory"));
+    }
+    if options.warm_start.is_some() {
+        return Err(String::from("plan starts each slot from the placement of the previous one"));
+    }
+    if options.jobs.is_some() || options.inputs.len() != 1 {
+        return Err(String::from("plan expects a single input"));
+    }
+    if options.inputs[0] == "-" && options.constraints.as_ref().is_some_and(|constraints| constraints == "-") {
+        return Err(String::from("stdin can only be read once"));
+    }
+    Ok(Command::Plan(PlanOptions {
+        solve: options,
+        migration_cost
+    }))
+}
+
+fn value<'a>(option: &str, value: Option<&'a String>) -> Result<&'a String, String> {
+    value.ok_or(format!("Missing value for {}", option))
+}
+
+fn parse_number<T: ::std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("Invalid value for {}: {}", option, value))
+}
@@ -0,0 +1,236 @@
+use algo::Mode;
+use cli::SolveOptions;
+use context::Limits;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// Solver settings read from a configuration file:
+//
+//     # Applies to every input
+//     [general]
+//     solvers = descent, best-video
+//     seed = 42
+//     time_limit = 10
+//     local_search = true
+//     jobs = 4
+//
+//     # Limits of a single solver, or of the local search
+//     [descent]
+//     time_limit = 2.5
+//     max_iterations = 1000
+//
+//     # Overrides for an input, matched by path or file name
+//     [input me_at_the_zoo.in]
+//     seed = 7
+//
+//     [input me_at_the_zoo.in local-search]
+//     max_iterations = 50
+//
+// Keys before the first section belong to [general].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub general: Settings,
+    pub inputs: BTreeMap<String, Settings>
+}
+
+// Every setting is optional, unset ones keep their command line or default value
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Settings {
+    pub modes: Option<Vec<Mode>>,
+    pub seed: Option<u64>,
+    pub time_limit: Option<Duration>,
+    pub max_iterations: Option<u64>,
+    pub local_search: Option<bool>,
+    pub jobs: Option<usize>,
+    // By solver name, local-search included
+    pub solver_limits: BTreeMap<String, Limits>
+}
+
+const LOCAL_SEARCH: &str = "local-search";
+
+enum Section {
+    General(Option<String>),
+    Solver(Option<String>, String)
+}
+
+pub fn parse_config(input: &str) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut section = Section::General(None);
+    for (index, line) in input.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: String| format!("line {}: {}", index + 1, message);
+
+        if line.starts_with('[') {
+            if !line.ends_with(']') {
+                return Err(error(format!("Invalid section header {}", line)));
+            }
+            section = parse_section(&line[1..line.len() - 1]).map_err(error)?;
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => return Err(error(format!("Expected key = value, got {}", line)))
+        };
+        match section {
+            Section::General(ref input) => {
+                let settings = match *input {
+                    Some(ref input) => config.inputs.entry(input.clone()).or_default(),
+                    None => &mut config.general
+                };
+                if key == "jobs" && input.is_some() {
+                    return Err(error(String::from("jobs can only be set in [general]")));
+                }
+                set_general(settings, key, value).map_err(error)?;
+            }
+            Section::Solver(ref input, ref solver) => {
+                let settings = match *input {
+                    Some(ref input) => config.inputs.entry(input.clone()).or_default(),
+                    None => &mut config.general
+                };
+                let limits = settings.solver_limits.entry(solver.clone()).or_default();
+                set_limit(limits, key, value).map_err(error)?;
+            }
+        }
+    }
+    Ok(config)
+}
+
+fn parse_section(header: &str) -> Result<Section, String> {
+    let words: Vec<&str> = header.split_whitespace().collect();
+    match words.as_slice() {
+        ["general"] => Ok(Section::General(None)),
+        [solver] => Ok(Section::Solver(None, solver_name(solver)?)),
+        ["input", input] => Ok(Section::General(Some(String::from(*input)))),
+        ["input", input, solver] => Ok(Section::Solver(Some(String::from(*input)), solver_name(solver)?)),
+        _ => Err(format!("Unknown section [{}]", header))
+    }
+}
+
+fn solver_name(name: &str) -> Result<String, String> {
+    match Mode::from_name(name) {
+        Some(_) => Ok(String::from(name)),
+        None if name == LOCAL_SEARCH => Ok(String::from(name)),
+        None => Err(format!("Unknown section [{}]", name))
+    }
+}
+
+fn set_general(settings: &mut Settings, key: &str, value: &str) -> Result<(), String> {
+    match key {
+        "solvers" => {
+            let modes = value.split(',')
+                .map(|name| Mode::from_name(name.trim()).ok_or(format!("Unknown solver {}", name.trim())))
+                .collect::<Result<Vec<Mode>, String>>()?;
+            settings.modes = Some(modes);
+        }
+        "seed" => settings.seed = Some(parse_value(key, value)?),
+        "time_limit" => settings.time_limit = Some(parse_seconds(key, value)?),
+        "max_iterations" => settings.max_iterations = Some(parse_value(key, value)?),
+        "local_search" => settings.local_search = Some(parse_value(key, value)?),
+        "jobs" => {
+            let jobs: usize = parse_value(key, value)?;
+            if jobs == 0 {
+                return Err(format!("Invalid value for {}: {}", key, value));
+            }
+            settings.jobs = Some(jobs);
+        }
+        _ => return Err(format!("Unknown key {}", key))
+    }
+    Ok(())
+}
+
+fn set_limit(limits: &mut Limits, key: &str, value: &str) -> Result<(), String> {
+    match key {
+        "time_limit" => limits.time_limit = Some(parse_seconds(key, value)?),
+        "max_iterations" => limits.max_iterations = Some(parse_value(key, value)?),
+        _ => return Err(format!("Unknown key {}", key))
+    }
+    Ok(())
+}
+
+fn parse_value<T: ::std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("Invalid value for {}: {}", key, value))
+}
+
+fn parse_seconds(key: &str, value: &str) -> Result<Duration, String> {
+    let seconds: f64 = parse_value(key, value)?;
+    if !seconds.is_finite() || seconds < 0. {
+        return Err(format!("Invalid value for {}: {}", key, value));
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+impl Config {
+    // The general settings with the overrides of the input applied
+    pub fn settings_for(&self, input: &str) -> Settings {
+        let name = input.rsplit('/').next().unwrap_or(input);
+        [name, input].iter()
+            .filter_map(|key| self.inputs.get(*key))
+            .fold(self.general.clone(), |settings, overrides| settings.merge(overrides))
+    }
+}
+
+impl Settings {
+    // The settings set in overrides win
+    pub fn merge(mut self, overrides: &Settings) -> Settings {
+        self.modes = overrides.modes.clone().or(self.modes);
+        self.seed = overrides.seed.or(self.seed);
+        self.time_limit = overrides.time_limit.or(self.time_limit);
+        self.max_iterations = overrides.max_iterations.or(self.max_iterations);
+        self.local_search = overrides.local_search.or(self.local_search);
+        self.jobs = overrides.jobs.or(self.jobs);
+        for (solver, limits) in &overrides.solver_limits {
+            let merged = self.solver_limits.entry(solver.clone()).or_default();
+            merged.time_limit = limits.time_limit.or(merged.time_limit);
+            merged.max_iterations = limits.max_iterations.or(merged.max_iterations);
+        }
+        self
+    }
+
+    // Sets the options that were not given on the command line
+    pub fn apply(&self, options: &mut SolveOptions) {
+        let command_line = options.command_line.clone();
+        let unset = |key: &str| !command_line.contains(key);
+        if let Some(modes) = self.modes.clone().filter(|_| unset("solvers")) {
+            options.modes = modes;
+        }
+        if let Some(seed) = self.seed.filter(|_| unset("seed")) {
+            options.seed = seed;
+        }
+        if let Some(time_limit) = self.time_limit.filter(|_| unset("time_limit")) {
+            options.time_limit = Some(time_limit);
+        }
+        if let Some(local_search) = self.local_search.filter(|_| unset("local_search")) {
+            options.local_search = local_search;
+        }
+        if let Some(jobs) = self.jobs.filter(|_| unset("jobs")) {
+            options.jobs = Some(jobs);
+        }
+    }
+}
+
+// The configuration a run uses, in the configuration file format
+pub fn format_effective(options: &SolveOptions, settings: &Settings) -> String {
+    let names: Vec<&str> = options.modes.iter().map(|mode| mode.name()).collect();
+    let mut returned = format!("[general]\nsolvers = {}\nseed = {}\n", names.join(", "), options.seed);
+    if let Some(time_limit) = options.time_limit {
+        returned += &format!("time_limit = {}\n", time_limit.as_secs_f64());
+    }
+    if let Some(max_iterations) = settings.max_iterations {
+        returned += &format!("max_iterations = {}\n", max_iterations);
+    }
+    returned += &format!("local_search = {}\n", options.local_search);
+    for (solver, limits) in &settings.solver_limits {
+        returned += &format!("[{}]\n", solver);
+        if let Some(time_limit) = limits.time_limit {
+            returned += &format!("time_limit = {}\n", time_limit.as_secs_f64());
+        }
+        if let Some(max_iterations) = limits.max_iterations {
+            returned += &format!("max_iterations = {}\n", max_iterations);
+        }
+    }
+    returned
+}
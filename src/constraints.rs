@@ -0,0 +1,185 @@
+use types::*;
+use placement::Placement;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+// Videos that must be in a cache, and videos that must never be in one, read
+// from a constraints file with one pair per line:
+//
+//     # Contract with the studio
+//     pin 3 0
+//     # Not licensed in the region of cache 1
+//     forbid 5 1
+//     # Every video in at most 4 caches, video 3 in 1 to 8 caches
+//     replicas * 0 4
+//     replicas 3 1 8
+//
+// where the first number is the video and the second one the cache, or the
+// minimum and maximum number of caches holding the video. A * maximum means no
+// maximum.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Constraints {
+    pub pinned: BTreeSet<(CacheId, VideoId)>,
+    pub forbidden: BTreeSet<(CacheId, VideoId)>,
+    // Limits of the videos without limits of their own
+    pub replicas: Option<ReplicaLimits>,
+    pub video_replicas: BTreeMap<VideoId, ReplicaLimits>
+}
+
+// Number of caches that may hold a video
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReplicaLimits {
+    pub min: i32,
+    // None when unlimited
+    pub max: Option<i32>
+}
+
+pub fn parse_constraints(input: &str) -> Result<Constraints, String> {
+    let mut constraints = Constraints::default();
+    for (index, line) in input.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: String| format!("line {}: {}", index + 1, message);
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [kind @ ("pin" | "forbid"), video, cache] => {
+                let pair = (CacheId(parse_id("cache", cache).map_err(error)?),
+                            VideoId(parse_id("video", video).map_err(error)?));
+                if *kind == "pin" {
+                    constraints.pinned.insert(pair);
+                } else {
+                    constraints.forbidden.insert(pair);
+                }
+            }
+            ["pin" | "forbid", ..] => return Err(error(format!("Expected pin|forbid VIDEO CACHE, got {}", line))),
+            ["replicas", video, min, max] => {
+                let limits = ReplicaLimits {
+                    min: parse_id("minimum", min).map_err(error)?,
+                    max: if *max == "*" { None } else { Some(parse_id("maximum", max).map_err(error)?) }
+                };
+                if limits.max.is_some_and(|max| max < limits.min) {
+                    return Err(error(format!("Maximum {} below minimum {}", max, min)));
+                }
+                if *video == "*" {
+                    constraints.replicas = Some(limits);
+                } else {
+                    constraints.video_replicas.insert(VideoId(parse_id("video", video).map_err(error)?), limits);
+                }
+            }
+            ["replicas", ..] => return Err(error(format!("Expected replicas VIDEO|* MIN MAX|*, got {}", line))),
+            words => return Err(error(format!("Unknown constraint {}", words[0])))
+        }
+    }
+    Ok(constraints)
+}
+
+fn parse_id(kind: &str, value: &str) -> Result<i32, String> {
+    value.parse::<i32>().ok()
+        .filter(|&id| id >= 0)
+        .ok_or(format!("Invalid {} {}", kind, value))
+}
+
+impl Constraints {
+    pub fn is_empty(&self) -> bool {
+        self.pinned.is_empty() && self.forbidden.is_empty() && !self.has_replica_limits()
+    }
+
+    pub fn has_replica_limits(&self) -> bool {
+        self.replicas.is_some() || !self.video_replicas.is_empty()
+    }
+
+    pub fn replica_limits(&self, video_id: VideoId) -> ReplicaLimits {
+        self.video_replicas.get(&video_id).cloned().or(self.replicas).unwrap_or_default()
+    }
+
+    // Same pins and forbidden videos, without replication limits
+    pub fn without_replica_limits(&self) -> Constraints {
+        Constraints {
+            pinned: self.pinned.clone(),
+            forbidden: self.forbidden.clone(),
+            ..Constraints::default()
+        }
+    }
+
+    // Whether a placement can meet the constraints: known ids, no pair both pinned
+    // and forbidden, pinned videos fitting in their cache, and enough caches able to
+    // hold the minimum number of copies of each video without too many pins
+    pub fn check(&self, instance: &Instance) -> Result<(), String> {
+        for &(cache_id, video_id) in self.pinned.iter().chain(&self.forbidden) {
+            if cache_id.0 >= instance.cache_info.count {
+                return Err(format!("Invalid cache {}", cache_id));
+            }
+            if video_id.index() >= instance.videos.len() {
+                return Err(format!("Invalid video {}", video_id));
+            }
+        }
+        if let Some(video_id) = self.video_replicas.keys().find(|video_id| video_id.index() >= instance.videos.len()) {
+            return Err(format!("Invalid video {}", video_id));
+        }
+        if let Some(&(cache_id, video_id)) = self.pinned.intersection(&self.forbidden).next() {
+            return Err(format!("Video {} is both pinned to and forbidden in cache {}", video_id, cache_id));
+        }
+        for cache_id in instance.cache_info.ids() {
+            let pinned: i64 = self.pinned.range((cache_id, VideoId(0))..=(cache_id, VideoId(i32::MAX)))
+                .map(|&(_, video_id)| instance.videos[video_id.index()].size as i64)
+                .sum();
+            let capacity = instance.cache_info.capacity(cache_id);
+            if pinned > capacity as i64 {
+                return Err(format!("Videos pinned to cache {} take {} but it can only hold {}", cache_id, pinned,
+                                   capacity));
+            }
+        }
+        if self.has_replica_limits() {
+            let mut pinned: Vec<i32> = vec![0; instance.videos.len()];
+            for &(_, video_id) in &self.pinned {
+                pinned[video_id.index()] += 1;
+            }
+            for video in &instance.videos {
+                let limits = self.replica_limits(video.id);
+                let pinned = pinned[video.id.index()];
+                if limits.max.is_some_and(|max| pinned > max) {
+                    return Err(format!("Video {} is pinned to {} caches but can be in at most {}", video.id, pinned,
+                                       limits.max.unwrap()));
+                }
+                let holders = instance.cache_info.caches.iter()
+                    .filter(|cache| cache.capacity >= video.size && !self.forbidden.contains(&(cache.id, video.id)))
+                    .count() as i32;
+                if holders < limits.min {
+                    return Err(format!("Video {} must be in {} caches but only {} can hold it", video.id, limits.min,
+                                       holders));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Whether the placement holds every pinned video and no forbidden one. The
+    // constraints must have been checked against the instance of the placement.
+    pub fn check_placement(&self, placement: &Placement) -> Result<(), String> {
+        if let Some(&(cache_id, video_id)) = self.pinned.iter()
+            .find(|&&(cache_id, video_id)| !placement.contains(cache_id, video_id)) {
+            return Err(format!("Video {} must be in cache {}", video_id, cache_id));
+        }
+        if let Some(&(cache_id, video_id)) = self.forbidden.iter()
+            .find(|&&(cache_id, video_id)| placement.contains(cache_id, video_id)) {
+            return Err(format!("Video {} cannot be in cache {}", video_id, cache_id));
+        }
+        if self.has_replica_limits() {
+            for video_id in (0..placement.video_count()).map(|index| VideoId(index as i32)) {
+                let limits = self.replica_limits(video_id);
+                let replicas = placement.replicas(video_id);
+                if replicas < limits.min {
+                    return Err(format!("Video {} is in {} caches but must be in at least {}", video_id, replicas,
+                                       limits.min));
+                }
+                if let Some(max) = limits.max.filter(|&max| replicas > max) {
+                    return Err(format!("Video {} is in {} caches but can be in at most {}", video_id, replicas, max));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use progress::{Reporter, SilentReporter, Tracker, Verbosity};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+// Limits of a single solver, overriding the ones of the context when set
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub time_limit: Option<Duration>,
+    pub max_iterations: Option<u64>
+}
+
+// Limits for a solver run. Long-running solvers check should_stop regularly
+// and return their best placement so far once it returns true. Progress goes
+// to the reporter, which is silent by default.
+#[derive(Clone)]
+pub struct Context {
+    time_limit: Option<Duration>,
+    deadline: Option<Instant>,
+    max_iterations: Option<u64>,
+    cancelled: Arc<AtomicBool>,
+    reporter: Arc<dyn Reporter>,
+    // By solver name, local-search included
+    solver_limits: BTreeMap<String, Limits>,
+    // Largest candidate table built by the solvers of this run
+    peak_candidates: Arc<AtomicUsize>
+}
+
+impl Context {
+    pub fn new() -> Context {
+        Context {
+            time_limit: None,
+            deadline: None,
+            max_iterations: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            reporter: Arc::new(SilentReporter),
+            solver_limits: BTreeMap::new(),
+            peak_candidates: Arc::new(AtomicUsize::new(0))
+        }
+    }
+
+    pub fn with_reporter(mut self, reporter: Arc<dyn Reporter>) -> Context {
+        self.reporter = reporter;
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Context {
+        self.time_limit = Some(time_limit);
+        self.deadline = Some(Instant::now() + time_limit);
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: u64) -> Context {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
+    pub fn with_solver_limits(mut self, solver: &str, limits: Limits) -> Context {
+        self.solver_limits.insert(String::from(solver), limits);
+        self
+    }
+
+    // Same limits and cancellation flag for a new run: the time limit is
+    // counted from now and the peak candidate count starts again from zero
+    pub fn restarted(&self) -> Context {
+        let mut context = self.clone();
+        context.deadline = self.time_limit.map(|time_limit| Instant::now() + time_limit);
+        context.peak_candidates = Arc::new(AtomicUsize::new(0));
+        context
+    }
+
+    // Restarted with the limits of the solver applied
+    pub fn for_solver(&self, solver: &str) -> Context {
+        let mut context = self.clone();
+        if let Some(limits) = self.solver_limits.get(solver) {
+            context.time_limit = limits.time_limit.or(self.time_limit);
+            context.max_iterations = limits.max_iterations.or(self.max_iterations);
+        }
+        context.restarted()
+    }
+
+    pub fn solver_limits(&self) -> &BTreeMap<String, Limits> {
+        &self.solver_limits
+    }
+
+    pub fn record_candidates(&self, count: usize) {
+        self.peak_candidates.fetch_max(count, Ordering::Relaxed);
+    }
+
+    pub fn peak_candidates(&self) -> usize {
+        self.peak_candidates.load(Ordering::Relaxed)
+    }
+
+    pub fn reporter(&self) -> &dyn Reporter {
+        &*self.reporter
+    }
+
+    // The message is only built when the reporter is verbose enough
+    pub fn log<F: FnOnce() -> String>(&self, verbosity: Verbosity, message: F) {
+        if verbosity != Verbosity::Silent && verbosity <= self.reporter.verbosity() {
+            self.reporter.message(&message());
+        }
+    }
+
+    pub fn tracker<'a>(&'a self, label: &'a str, total: Option<u64>) -> Tracker<'a> {
+        Tracker::new(&*self.reporter, label, total)
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit
+    }
+
+    pub fn max_iterations(&self) -> Option<u64> {
+        self.max_iterations
+    }
+
+    // Cancels every run sharing this context, from any thread
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    pub fn should_stop(&self, iteration: u64) -> bool {
+        self.is_cancelled()
+            || self.max_iterations.is_some_and(|max_iterations| iteration >= max_iterations)
+            || self.is_expired()
+    }
+}
+
+impl Default for Context {
+    fn default() -> Context {
+        Context::new()
+    }
+}
@@ -0,0 +1,129 @@
+use types::*;
+use tiers::effective_latencies;
+
+// Compressed sparse rows: the items of row i are items[offsets[i]..offsets[i + 1]]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Csr<T> {
+    offsets: Vec<usize>,
+    items: Vec<T>
+}
+
+impl<T: Copy + Default> Csr<T> {
+    // Pairs are (row, item), in any order. Items of a row keep their relative order.
+    // Pairs are iterated twice, to count the items of each row and then to put them
+    // in place, so that no other copy of the items is made.
+    pub fn from_pairs<I>(row_count: usize, pairs: I) -> Csr<T>
+        where I: IntoIterator<Item = (usize, T)>, I::IntoIter: Clone {
+        let pairs = pairs.into_iter();
+        let mut offsets: Vec<usize> = vec![0; row_count + 1];
+        for (row, _) in pairs.clone() {
+            offsets[row + 1] += 1;
+        }
+        for row in 0..row_count {
+            offsets[row + 1] += offsets[row];
+        }
+        let mut items: Vec<T> = vec![T::default(); offsets[row_count]];
+        let mut next: Vec<usize> = offsets[..row_count].to_vec();
+        for (row, item) in pairs {
+            items[next[row]] = item;
+            next[row] += 1;
+        }
+        Csr {
+            offsets,
+            items
+        }
+    }
+}
+
+impl<T> Csr<T> {
+    pub fn row_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.items[self.offsets[row]..self.offsets[row + 1]]
+    }
+
+    // Position of the first item of the row in items
+    pub fn offset(&self, row: usize) -> usize {
+        self.offsets[row]
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+}
+
+// Instance laid out for the solvers: vectors indexed by id, and adjacency lists
+// sorted by id instead of nested maps
+pub struct DenseInstance {
+    pub video_sizes: Vec<i32>,
+    pub cache_capacities: Vec<i32>,
+    pub datacenter_latencies: Vec<i32>,
+    // Per endpoint, the reached caches with their effective latency
+    pub endpoint_caches: Csr<(CacheId, i32)>,
+    // Per cache, the endpoints reaching it with their effective latency
+    pub cache_endpoints: Csr<(EndpointId, i32)>,
+    // Per video, the requests of each endpoint, duplicate request lines summed
+    pub video_demand: Csr<(EndpointId, i64)>,
+    // Per endpoint, the requested videos, with the position of the requests in
+    // video_demand rather than a second copy of them
+    pub endpoint_demand: Csr<(VideoId, u32)>
+}
+
+impl DenseInstance {
+    pub fn new(cache_info: &CacheInfo, videos: &[Video], endpoints: &[Endpoint], requests: &[Request]) -> DenseInstance {
+        // Endpoints also reach the parents of their caches
+        let links: Vec<(EndpointId, CacheId, i32)> = endpoints.iter()
+            .flat_map(|endpoint| {
+                effective_latencies(cache_info, endpoint).into_iter()
+                    .map(move |(cache_id, latency)| (endpoint.id, cache_id, latency))
+            })
+            .collect();
+
+        let mut demand: Vec<(VideoId, EndpointId, i64)> = requests.iter()
+            .map(|request| (request.video_id, request.endpoint_id, request.count as i64))
+            .collect();
+        demand.sort_by_key(|&(video_id, endpoint_id, _)| (video_id, endpoint_id));
+        demand.dedup_by(|current, kept| {
+            let duplicate = (current.0, current.1) == (kept.0, kept.1);
+            if duplicate {
+                kept.2 += current.2;
+            }
+            duplicate
+        });
+
+        DenseInstance {
+            video_sizes: videos.iter().map(|video| video.size).collect(),
+            cache_capacities: cache_info.caches.iter().map(|cache| cache.capacity).collect(),
+            datacenter_latencies: endpoints.iter().map(|endpoint| endpoint.datacenter_latency).collect(),
+            endpoint_caches: Csr::from_pairs(endpoints.len(), links.iter()
+                .map(|&(endpoint_id, cache_id, latency)| (endpoint_id.index(), (cache_id, latency)))),
+            cache_endpoints: Csr::from_pairs(cache_info.count as usize, links.iter()
+                .map(|&(endpoint_id, cache_id, latency)| (cache_id.index(), (endpoint_id, latency)))),
+            video_demand: Csr::from_pairs(videos.len(), demand.iter()
+                .map(|&(video_id, endpoint_id, count)| (video_id.index(), (endpoint_id, count)))),
+            // Demand is sorted by video, as video_demand. Positions fit in u32 since
+            // there are fewer demands than requests.
+            endpoint_demand: Csr::from_pairs(endpoints.len(), demand.iter().enumerate()
+                .map(|(position, &(video_id, endpoint_id, _))| (endpoint_id.index(), (video_id, position as u32))))
+        }
+    }
+
+    pub fn from_instance(instance: &Instance) -> DenseInstance {
+        DenseInstance::new(&instance.cache_info, &instance.videos, &instance.endpoints, &instance.requests)
+    }
+
+    // Requests at a position in endpoint_demand
+    pub fn endpoint_requests(&self, position: usize) -> i64 {
+        self.video_demand.items()[self.endpoint_demand.items()[position].1 as usize].1
+    }
+
+    // Position in endpoint_demand of the requests of the endpoint for the video
+    pub fn demand_position(&self, endpoint_id: EndpointId, video_id: VideoId) -> Option<usize> {
+        self.endpoint_demand.row(endpoint_id.index())
+            .binary_search_by_key(&video_id, |&(video_id, _)| video_id)
+            .ok()
+            .map(|index| self.endpoint_demand.offset(endpoint_id.index()) + index)
+    }
+}
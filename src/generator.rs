@@ -0,0 +1,119 @@
+use types::*;
+use rng::Rng;
+use std::cmp::min;
+use std::collections::BTreeMap;
+
+pub struct GeneratorParams {
+    pub video_count: i32,
+    pub endpoint_count: i32,
+    pub request_count: i32,
+    pub cache_count: i32,
+    pub cache_capacity: i32,
+    pub max_video_size: i32,
+    pub max_datacenter_latency: i32,
+    pub max_requests: i32,
+    // Probability for an endpoint to be connected to a given cache
+    pub connectivity: f64
+}
+
+impl GeneratorParams {
+    pub fn new(video_count: i32, endpoint_count: i32, request_count: i32, cache_count: i32,
+               cache_capacity: i32) -> GeneratorParams {
+        GeneratorParams {
+            video_count,
+            endpoint_count,
+            request_count,
+            cache_count,
+            cache_capacity,
+            max_video_size: 1000,
+            max_datacenter_latency: 4000,
+            max_requests: 10000,
+            connectivity: 0.5
+        }
+    }
+}
+
+// Random instance following the Hash Code limits. Video popularity is skewed,
+// the n-th video being requested about 1 / n as often as the first one.
+pub fn generate(seed: u64, params: &GeneratorParams) -> Instance {
+    let mut rng = Rng::new(seed);
+
+    let videos: Vec<Video> = (0..params.video_count)
+        .map(|id| Video::new(VideoId(id), rng.range(1, params.max_video_size as i64 + 1) as i32))
+        .collect();
+
+    let endpoints: Vec<Endpoint> = (0..params.endpoint_count).map(|id| {
+        let datacenter_latency = rng.range(2, params.max_datacenter_latency as i64 + 1) as i32;
+        let mut cache_to_latency: BTreeMap<CacheId, i32> = BTreeMap::new();
+        for cache_id in 0..params.cache_count {
+            if rng.chance(params.connectivity) {
+                cache_to_latency.insert(CacheId(cache_id), rng.range(1, datacenter_latency as i64) as i32);
+            }
+        }
+        Endpoint::new(EndpointId(id), datacenter_latency, cache_to_latency)
+    }).collect();
+
+    let mut popularity: Vec<u64> = (0..params.video_count as u64).map(|rank| 1_000_000 / (rank + 1)).collect();
+    rng.shuffle(&mut popularity);
+
+    let request_count = min(params.request_count as i64, params.video_count as i64 * params.endpoint_count as i64);
+    let mut counts: BTreeMap<(VideoId, EndpointId), i32> = BTreeMap::new();
+    while (counts.len() as i64) < request_count {
+        let video_id = VideoId(rng.weighted_choice(&popularity).unwrap() as i32);
+        let endpoint_id = EndpointId(rng.range(0, params.endpoint_count as i64) as i32);
+        let count = rng.range(1, params.max_requests as i64 + 1) as i32;
+        counts.entry((video_id, endpoint_id)).or_insert(count);
+    }
+    let requests: Vec<Request> = counts.into_iter()
+        .map(|((video_id, endpoint_id), count)| Request::new(video_id, endpoint_id, count))
+        .collect();
+
+    Instance::new(CacheInfo::new(params.cache_count, params.cache_capacity), videos, endpoints, requests)
+}
+
+// Writes an instance in the input format read by the parser, the extended one
+// when caches have different capacities, parents or throughput limits, or when
+// requests are sliced in time
+pub fn format_instance(instance: &Instance) -> String {
+    let header = format!("{} {} {} {} ", instance.videos.len(), instance.endpoints.len(),
+                         instance.requests.len(), instance.cache_info.count);
+    let header = match instance.cache_info.uniform_capacity() {
+        Some(capacity) => header + &format!("{}\n", capacity),
+        None => {
+            let capacities: Vec<String> = instance.cache_info.caches.iter()
+                .map(|cache| format!("{}", cache.capacity))
+                .collect();
+            header + "*\n" + &capacities.join(" ") + "\n"
+        }
+    };
+    let sizes: Vec<String> = instance.videos.iter().map(|video| format!("{}", video.size)).collect();
+    let returned = header + &sizes.join(" ") + "\n";
+    let returned = instance.endpoints.iter().fold(returned, |result, endpoint| {
+        let endpoint_header = format!("{} {}\n", endpoint.datacenter_latency, endpoint.cache_to_latency.len());
+        endpoint.cache_to_latency.iter().fold(result + &endpoint_header, |result, (cache_id, latency)| {
+            result + &format!("{} {}\n", cache_id, latency)
+        })
+    });
+    let sliced = instance.slot_count() > 1;
+    let returned = instance.requests.iter().fold(returned, |result, request| {
+        let slot = if sliced { format!(" {}", request.slot) } else { String::new() };
+        result + &format!("{} {} {}{}\n", request.video_id, request.endpoint_id, request.count, slot)
+    });
+    let cache_info = &instance.cache_info;
+    if !cache_info.has_tiers() && !cache_info.has_throughput_limits() {
+        return returned;
+    }
+    let tiers: Vec<String> = cache_info.caches.iter()
+        .filter_map(|cache| {
+            cache.parent.map(|(parent_id, latency)| format!("{} {} {}\n", cache.id, parent_id, latency))
+        })
+        .collect();
+    let returned = returned + &format!("{}\n", tiers.len()) + &tiers.concat();
+    if !cache_info.has_throughput_limits() {
+        return returned;
+    }
+    let throughputs: Vec<String> = cache_info.caches.iter()
+        .map(|cache| cache.throughput.map_or(String::from("*"), |throughput| format!("{}", throughput)))
+        .collect();
+    returned + &throughputs.join(" ") + "\n"
+}
@@ -1,4 +1,25 @@
 pub mod types;
+pub mod dense;
+pub mod placement;
+pub mod constraints;
+pub mod tiers;
+pub mod routing;
+pub mod schedule;
 pub mod parser;
 pub mod output;
-pub mod algo;
\ No newline at end of file
+pub mod algo;
+pub mod score;
+pub mod search;
+pub mod portfolio;
+pub mod rng;
+pub mod generator;
+pub mod context;
+pub mod progress;
+pub mod reduce;
+pub mod cli;
+pub mod stats;
+pub mod bench;
+pub mod batch;
+pub mod config;
+pub mod zip;
+pub mod package;
@@ -1,42 +1,481 @@
 extern crate videos;
-use std::fs::File;
-use std::io::{Read, Write};
-use videos::parser::parse;
-use videos::output::produce_output;
-use videos::algo::{Mode, algo};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use videos::types::Instance;
+use videos::placement::Placement;
+use videos::constraints::{Constraints, parse_constraints};
+use videos::context::Context;
+use videos::progress::{Reporter, ConsoleReporter, PrefixedReporter, Verbosity};
+use videos::parser::{parse, parse_output};
+use videos::score::{score, evaluate, format_evaluation};
+use videos::portfolio::{portfolio, reduced_portfolio, unlimited_score, best, format_table};
+use videos::bench::{BenchResult, list_inputs, format_matrix};
+use videos::batch::{BatchResult, run_batch, prefix_lines, format_summary};
+use videos::stats::{stats, format_report, format_key_values};
+use videos::config::{Config, Settings, parse_config, format_effective};
+use videos::package::{submission_name, source_zip};
+use videos::schedule::{plan, slot_values, format_schedule};
+use videos::cli::{Command, SolveOptions, ScoreOptions, StatsOptions, PackageOptions, PlanOptions, USAGE, parse_args,
+                  output_path, slot_output_path};
 
 fn main() {
-    //let files = vec!["kittens.in", "me_at_the_zoo.in", "trending_today.in", "videos_worth_spreading.in"];
-    //let files = vec!["me_at_the_zoo.in"];
-    let files = vec!["kittens.in"];
-    for file in files {
-        println!("Processing file {}", file);
-        process(file).unwrap();
+    let args: Vec<String> = env::args().skip(1).collect();
+    match parse_args(&args) {
+        Ok(Command::Help) => print!("{}", USAGE),
+        Ok(Command::Solve(options)) => process::exit(solve(&options)),
+        Ok(Command::Score(options)) => process::exit(score_submission(&options)),
+        Ok(Command::Stats(options)) => process::exit(describe(&options)),
+        Ok(Command::Bench(options)) => process::exit(bench(&options)),
+        Ok(Command::Package(options)) => process::exit(package(&options)),
+        Ok(Command::Plan(options)) => process::exit(plan_slots(&options)),
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
     }
 }
 
-fn process(filename: &str) -> Result<(), String> {
-    let in_filename = format!("resources/{}", filename);
-    let out_filename = format!("output/{}.out", filename);
-    File::open(in_filename)
+fn load_config(options: &SolveOptions) -> Result<Config, String> {
+    match options.config {
+        Some(ref filename) => read_file(filename)
+            .and_then(|contents| parse_config(&contents))
+            .map_err(|err| format!("{}: {}", filename, err)),
+        None => Ok(Config::default())
+    }
+}
+
+fn context(options: &SolveOptions, settings: &Settings, reporter: Arc<dyn Reporter>) -> Context {
+    let mut context = Context::new().with_reporter(reporter);
+    if let Some(time_limit) = options.time_limit {
+        context = context.with_time_limit(time_limit);
+    }
+    if let Some(max_iterations) = settings.max_iterations {
+        context = context.with_max_iterations(max_iterations);
+    }
+    settings.solver_limits.iter().fold(context, |context, (solver, limits)| {
+        context.with_solver_limits(solver, limits.clone())
+    })
+}
+
+// The options and context of an input, with its configuration applied and echoed
+fn configure(options: &SolveOptions, config: &Config, input: &str, reporter: Arc<dyn Reporter>)
+             -> (SolveOptions, Context) {
+    let settings = config.settings_for(input);
+    let mut options = options.clone();
+    settings.apply(&mut options);
+    let context = context(&options, &settings, reporter);
+    context.log(Verbosity::Normal, || String::from("Configuration:"));
+    for line in format_effective(&options, &settings).lines() {
+        context.log(Verbosity::Normal, || String::from(line));
+    }
+    (options, context)
+}
+
+// Solves the inputs concurrently. With several inputs, logs and results are
+// prefixed with the file name and followed by a summary.
+fn solve(options: &SolveOptions) -> i32 {
+    let config = match load_config(options) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    let constraints = match load_constraints(&options.constraints) {
+        Ok(constraints) => constraints,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    let mut options = options.clone();
+    config.general.apply(&mut options);
+
+    let reporter: Arc<dyn Reporter> = Arc::new(ConsoleReporter::new(options.verbosity));
+    let batch = options.inputs.len() > 1;
+    let jobs = options.jobs.unwrap_or_else(|| thread::available_parallelism().map(|jobs| jobs.get()).unwrap_or(1));
+
+    let results = run_batch(&options.inputs, jobs, |input| {
+        let reporter = if batch {
+            Arc::new(PrefixedReporter::new(input, reporter.clone()))
+        } else {
+            reporter.clone()
+        };
+        let (options, context) = configure(&options, &config, input, reporter);
+        context.log(Verbosity::Normal, || format!("Processing file {}", input));
+        let mut out = String::new();
+        let result = process(&context, &options, &constraints, input, &mut out);
+        if options.output_for(input) == "-" {
+            eprint!("{}", out);
+        } else {
+            print!("{}", if batch { prefix_lines(input, &out) } else { out });
+        }
+        if let Err(ref err) = result {
+            eprintln!("{}: {}", input, err);
+        }
+        BatchResult {
+            file: String::from(input),
+            result
+        }
+    });
+
+    if batch {
+        print!("\n{}", format_summary(&results));
+    }
+    if results.iter().any(|result| result.result.is_err()) { 1 } else { 0 }
+}
+
+fn score_submission(options: &ScoreOptions) -> i32 {
+    let constraints = match load_constraints(&options.constraints) {
+        Ok(constraints) => constraints,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    let result = read_file(&options.input)
+        .map_err(|err| format!("{}: {}", options.input, err))
+        .and_then(|contents| {
+            parse(&contents).ok_or(format!("Unable to parse input {}", options.input))
+        }).and_then(|(cache_info, videos, endpoints, requests)| {
+            let instance = Instance::new(cache_info, videos, endpoints, requests);
+            read_file(&options.submission)
+                .map_err(|err| format!("{}: {}", options.submission, err))
+                .and_then(|contents| {
+                    parse_output(&contents).ok_or(format!("Unable to parse submission {}", options.submission))
+                }).map(|placement| (instance, placement))
+        });
+
+    match result {
+        Ok((instance, placement)) => match Placement::from_map(&instance, &placement).and_then(|placement| {
+            constraints.check(&instance)
+                .and_then(|_| constraints.check_placement(&placement))
+                .map(|_| placement)
+        }) {
+            Ok(placement) => {
+                println!("Valid submission");
+                print!("{}", format_evaluation(&instance, &evaluate(&instance, &placement)));
+                0
+            }
+            Err(err) => {
+                println!("Invalid submission: {}", err);
+                1
+            }
+        },
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        }
+    }
+}
+
+fn describe(options: &StatsOptions) -> i32 {
+    let mut status = 0;
+    for input in &options.inputs {
+        let result = read_file(input)
+            .and_then(|contents| parse(&contents).ok_or(String::from("Unable to parse input")));
+        match result {
+            Ok((cache_info, videos, endpoints, requests)) => {
+                let instance_stats = stats(&Instance::new(cache_info, videos, endpoints, requests));
+                if options.key_value {
+                    print!("input={}\n{}", input, format_key_values(&instance_stats));
+                } else {
+                    print!("{}\n{}\n", input, format_report(&instance_stats));
+                }
+            }
+            Err(err) => {
+                eprintln!("{}: {}", input, err);
+                status = 1;
+            }
+        }
+    }
+    status
+}
+
+fn bench(options: &SolveOptions) -> i32 {
+    let config = match load_config(options) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    let constraints = match load_constraints(&options.constraints) {
+        Ok(constraints) => constraints,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    let reporter: Arc<dyn Reporter> = Arc::new(ConsoleReporter::new(options.verbosity));
+    let (inputs, mut status) = expand_inputs(&options.inputs);
+
+    let mut results: Vec<BenchResult> = Vec::new();
+    for input in inputs {
+        let (options, context) = configure(options, &config, &input, reporter.clone());
+        context.log(Verbosity::Normal, || format!("Benchmarking file {}", input));
+        let result = read_file(&input)
+            .and_then(|contents| parse(&contents).ok_or(String::from("Unable to parse input")))
+            .and_then(|(cache_info, videos, endpoints, requests)| {
+                let instance = Instance::new(cache_info, videos, endpoints, requests);
+                Placement::with_constraints(&instance, &constraints).map(|initial| (instance, initial))
+            });
+        match result {
+            Ok((instance, initial)) => {
+                let runs = portfolio(&context, &instance, &initial, &options.modes, options.local_search, options.seed);
+                results.push(BenchResult {
+                    file: input,
+                    runs
+                });
+            }
+            Err(err) => {
+                eprintln!("{}: {}", input, err);
+                status = 1;
+            }
+        }
+    }
+
+    print!("{}", format_matrix(&results));
+    status
+}
+
+// Replaces directories by the .in files they contain, with 1 as status if one
+// cannot be listed
+fn expand_inputs(arguments: &[String]) -> (Vec<String>, i32) {
+    let mut status = 0;
+    let mut inputs: Vec<String> = Vec::new();
+    for input in arguments {
+        if Path::new(input).is_dir() {
+            match list_inputs(input) {
+                Ok(listed) => inputs.extend(listed),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    status = 1;
+                }
+            }
+        } else {
+            inputs.push(input.clone());
+        }
+    }
+    (inputs, status)
+}
+
+// Copies the best valid placement of each input to the destination and archives
+// the sources next to them
+fn package(options: &PackageOptions) -> i32 {
+    let (inputs, mut status) = expand_inputs(&options.inputs);
+    if let Err(err) = fs::create_dir_all(&options.destination) {
+        eprintln!("{}: {}", options.destination, err);
+        return 1;
+    }
+
+    let mut total = 0;
+    println!("{:<40} {:<40} {:>12}", "input", "placement", "score");
+    for input in &inputs {
+        let result = read_file(input)
+            .and_then(|contents| parse(&contents).ok_or(String::from("Unable to parse input")))
+            .and_then(|(cache_info, videos, endpoints, requests)| {
+                let instance = Instance::new(cache_info, videos, endpoints, requests);
+                let mut best: Option<(String, i64, Placement)> = None;
+                for output_dir in &options.output_dirs {
+                    let filename = output_path(output_dir, input);
+                    if !Path::new(&filename).exists() {
+                        continue;
+                    }
+                    match warm_start(&instance, &filename) {
+                        Ok(placement) => {
+                            let placement_score = score(&instance, &placement);
+                            if best.as_ref().is_none_or(|&(_, best_score, _)| placement_score > best_score) {
+                                best = Some((filename, placement_score, placement));
+                            }
+                        }
+                        Err(err) => eprintln!("{}", err)
+                    }
+                }
+                best.ok_or(String::from("No valid placement found"))
+            }).and_then(|(filename, placement_score, placement)| {
+                let destination = format!("{}/{}", options.destination.trim_end_matches('/'),
+                                          submission_name(input));
+                fs::write(&destination, placement.to_submission())
+                    .map_err(|err| format!("{}: {}", destination, err))
+                    .map(|_| (filename, placement_score))
+            });
+
+        match result {
+            Ok((filename, placement_score)) => {
+                println!("{:<40} {:<40} {:>12}", input, filename, placement_score);
+                total += placement_score;
+            }
+            Err(err) => {
+                eprintln!("{}: {}", input, err);
+                status = 1;
+            }
+        }
+    }
+    println!("{:<40} {:<40} {:>12}", "total", "", total);
+
+    let archive = format!("{}/source.zip", options.destination.trim_end_matches('/'));
+    match source_zip(&options.source).and_then(|zip| fs::write(&archive, zip).map_err(|err| format!("{}: {}", archive, err))) {
+        Ok(()) => println!("Sources written to {}", archive),
+        Err(err) => {
+            eprintln!("{}", err);
+            status = 1;
+        }
+    }
+    status
+}
+
+// Writes the placement of every time slot of the input
+fn plan_slots(options: &PlanOptions) -> i32 {
+    let config = match load_config(&options.solve) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    let constraints = match load_constraints(&options.solve.constraints) {
+        Ok(constraints) => constraints,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    let input = &options.solve.inputs[0];
+    let reporter = Arc::new(ConsoleReporter::new(options.solve.verbosity));
+    let (solve_options, context) = configure(&options.solve, &config, input, reporter);
+    let mode = match solve_options.modes.as_slice() {
+        &[mode] => mode,
+        _ => {
+            eprintln!("{}: plan runs a single solver", input);
+            return 2;
+        }
+    };
+    let result = read_file(input)
+        .and_then(|contents| parse(&contents).ok_or(String::from("Unable to parse input")))
+        .and_then(|(cache_info, videos, endpoints, requests)| {
+            let instance = Instance::new(cache_info, videos, endpoints, requests);
+            let initial = Placement::with_constraints(&instance, &constraints)?;
+            context.log(Verbosity::Normal, || {
+                format!("Planning {} slots of {} with {}", instance.slot_count(), input, mode.name())
+            });
+            let placements = plan(&context, &instance, &initial, mode, solve_options.local_search, solve_options.seed,
+                                  options.migration_cost);
+            let output_dir = &solve_options.output_dir;
+            fs::create_dir_all(output_dir).map_err(|err| format!("{}: {}", output_dir, err))?;
+            for (slot, placement) in placements.iter().enumerate() {
+                let filename = slot_output_path(output_dir, input, slot);
+                fs::write(&filename, placement.to_submission()).map_err(|err| format!("{}: {}", filename, err))?;
+            }
+            Ok(slot_values(&instance, &placements))
+        });
+
+    match result {
+        Ok(values) => {
+            print!("{}", format_schedule(&values, options.migration_cost));
+            0
+        }
+        Err(err) => {
+            eprintln!("{}: {}", input, err);
+            1
+        }
+    }
+}
+
+// Reads stdin for -
+fn read_file(filename: &str) -> Result<String, String> {
+    if filename == "-" {
+        let mut contents = String::new();
+        return io::stdin().read_to_string(&mut contents)
+            .map_err(|err| err.to_string())
+            .map(|_| contents);
+    }
+    File::open(filename)
         .map_err(|err| err.to_string())
         .and_then(|mut file| {
             let mut contents = String::new();
             file.read_to_string(&mut contents)
                 .map_err(|err| err.to_string())
                 .map(|_| contents)
-        }).and_then(|contents| {
-            parse(&*contents).ok_or(String::from("Unable to parse input"))
+        })
+}
+
+// No constraints without a file
+fn load_constraints(filename: &Option<String>) -> Result<Constraints, String> {
+    match *filename {
+        Some(ref filename) => read_file(filename)
+            .and_then(|contents| parse_constraints(&contents))
+            .map_err(|err| format!("{}: {}", filename, err)),
+        None => Ok(Constraints::default())
+    }
+}
+
+// Loads an existing submission to start from, checking that it fits the instance
+fn warm_start(instance: &Instance, filename: &str) -> Result<Placement, String> {
+    read_file(filename)
+        .and_then(|contents| Placement::from_submission(instance, &contents))
+        .map_err(|err| format!("{}: {}", filename, err))
+}
+
+// Results are written to out rather than stdout, so that concurrent inputs
+// do not interleave. Returns the best score.
+fn process(context: &Context, options: &SolveOptions, constraints: &Constraints, in_filename: &str, out: &mut String)
+           -> Result<i64, String> {
+    let out_filename = options.output_for(in_filename);
+    read_file(in_filename)
+        .and_then(|contents| {
+            parse(&contents).ok_or(String::from("Unable to parse input"))
         }).and_then(|(cache_info, videos, endpoints, requests)| {
-            let output = algo(Mode::DescentAmend, cache_info, videos, endpoints, requests);
-            let output_string = produce_output(output);
+            let instance = Instance::new(cache_info, videos, endpoints, requests);
+            let mut initial = Placement::with_constraints(&instance, constraints)?;
+            if let Some(ref filename) = options.warm_start {
+                // Videos of the submission that break the constraints are dropped
+                initial.extend(&warm_start(&instance, filename)?);
+                context.log(Verbosity::Normal, || {
+                    format!("Starting from {} with score {}", filename, score(&instance, &initial))
+                });
+            }
 
-            File::create(out_filename)
-                .map_err(|err| err.to_string())
+            // The output file is only read to compare with, it may come from another instance
+            if out_filename != "-" && Path::new(&out_filename).exists() {
+                match warm_start(&instance, &out_filename) {
+                    Ok(previous) => *out += &format!("Previous score in {}: {}\n", out_filename,
+                                                     score(&instance, &previous)),
+                    Err(err) => *out += &format!("Warning: overwriting {}\n", err)
+                }
+            }
+
+            let runs = reduced_portfolio(context, &instance, &initial, &options.modes, options.local_search,
+                                         options.seed)?;
+            *out += &format_table(&runs);
+
+            let run = best(&runs).ok_or(String::from("No strategy produced a valid placement"))?;
+            *out += &format!("Best strategy: {} with score {} (seed {})\n", run.name(), run.score, run.seed);
+            if constraints.has_replica_limits() && options.replication_report {
+                let unlimited = unlimited_score(context, &instance, &initial, run)?;
+                *out += &format!("Without replication limits: score {} ({:+})\n", unlimited, unlimited - run.score);
+            }
+            let output_string = run.placement.to_submission();
+            if out_filename == "-" {
+                return io::stdout().write_all(output_string.as_bytes())
+                    .map_err(|err| err.to_string())
+                    .map(|_| run.score);
+            }
+
+            if let Some(parent) = Path::new(&out_filename).parent() {
+                fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+            }
+            File::create(&out_filename)
+                .map_err(|err| format!("{}: {}", out_filename, err))
                 .and_then(|mut file| {
                     file.write_all(output_string.as_bytes())
                         .map_err(|err| err.to_string())
-                        .map(|_| ())
+                        .map(|_| run.score)
                 })
         })
-}
\ No newline at end of file
+}
@@ -1,10 +1,10 @@
-use std::collections::BTreeMap;
-use std::collections::BTreeSet;
+use types::*;
+use placement::Placement;
 
-pub fn produce_output(result: BTreeMap<i32, BTreeSet<i32>>) -> String {
+pub fn produce_output(result: &Placement) -> String {
     let all: Vec<CacheAndVideos> = result.iter()
         .filter(|&(_, video_ids)| !video_ids.is_empty())
-        .map(|(&cache_id, ref videos_id)| CacheAndVideos::new(cache_id, videos_id.iter().cloned().collect()))
+        .map(|(cache_id, videos_id)| CacheAndVideos::new(cache_id, videos_id.iter().cloned().collect()))
         .collect();
     let returned: String = format!("{}\n", all.len());
     all.iter().fold(returned, |result, cache_and_video| {
@@ -17,15 +17,15 @@ pub fn produce_output(result: BTreeMap<i32, BTreeSet<i32>>) -> String {
 }
 
 struct CacheAndVideos {
-    cache_id: i32,
-    video_ids: Vec<i32>
+    cache_id: CacheId,
+    video_ids: Vec<VideoId>
 }
 
 impl CacheAndVideos {
-    fn new(cache_id: i32, video_ids: Vec<i32>) -> CacheAndVideos {
+    fn new(cache_id: CacheId, video_ids: Vec<VideoId>) -> CacheAndVideos {
         CacheAndVideos {
-            cache_id: cache_id,
-            video_ids: video_ids
+            cache_id,
+            video_ids
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,46 @@
+use zip::ZipWriter;
+use std::fs;
+use std::path::Path;
+
+// What goes in the source archive, relative to the crate root
+const SOURCES: [&str; 3] = ["Cargo.toml", "src", "tests"];
+
+// Name of the submission of an input: resources/me_at_the_zoo.in gives me_at_the_zoo.out
+pub fn submission_name(input: &str) -> String {
+    let name = input.rsplit('/').next().unwrap_or(input);
+    format!("{}.out", name.strip_suffix(".in").unwrap_or(name))
+}
+
+// Files to archive, relative to root and sorted
+pub fn source_files(root: &str) -> Result<Vec<String>, String> {
+    let mut files: Vec<String> = Vec::new();
+    for source in SOURCES.iter() {
+        list_files(Path::new(root), Path::new(source), &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn list_files(root: &Path, relative: &Path, files: &mut Vec<String>) -> Result<(), String> {
+    let path = root.join(relative);
+    if path.is_file() {
+        files.push(relative.to_string_lossy().into_owned());
+        return Ok(());
+    }
+    let entries = fs::read_dir(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    for entry in entries {
+        let entry = entry.map_err(|err| format!("{}: {}", path.display(), err))?;
+        list_files(root, &relative.join(entry.file_name()), files)?;
+    }
+    Ok(())
+}
+
+pub fn source_zip(root: &str) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new();
+    for file in source_files(root)? {
+        let path = Path::new(root).join(&file);
+        let contents = fs::read(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        zip.add(&file, &contents)?;
+    }
+    zip.finish()
+}
@@ -1,34 +1,75 @@
 use types::*;
+use tiers::is_acyclic;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 
-pub fn parse(input: &str) -> Option<(CacheInfo, Vec<Video>, Vec<Endpoint>, Vec<Request>)> {
+pub type Parsed = (CacheInfo, Vec<Video>, Vec<Endpoint>, Vec<Request>);
+
+// Besides the standard format, reads an extended one where the cache capacity
+// of the header is * and is followed by a line with the capacity of each cache.
+// The requests may be followed by cache tiers: a line with the number of caches
+// backed by a parent cache, then a line per such cache with its id, the id of
+// its parent and the latency between them. The tiers may be followed by a line
+// with the number of requests each cache can serve, * for no limit. Requests
+// may have a fourth value, the time slot they belong to, below MAX_SLOTS.
+pub fn parse(input: &str) -> Option<Parsed> {
     let mut header = Header::new();
+    let mut cache_info = CacheInfo::new(0, 0);
     let mut videos: Vec<Video> = Vec::new();
     let mut endpoints: Vec<Endpoint> = Vec::new();
+    let mut requests: Vec<Request> = Vec::new();
     let mut splitted = input.trim().split('\n');
 
     splitted.next()
         .and_then(parse_header)
         .and_then(|parsed_header| {
-            println!("Parsing headers");
             header = parsed_header;
+            match header.cache_capacity {
+                Some(capacity) => Some(CacheInfo::new(header.cache_count, capacity)),
+                None => splitted.next().and_then(|capacities| parse_capacities(capacities, header.cache_count))
+            }
+        })
+        .and_then(|parsed_cache_info| {
+            cache_info = parsed_cache_info;
             splitted.next()
         })
         .and_then(|videos| {
-            println!("Parsing videos");
             parse_videos(videos, header.video_count)
         })
         .and_then(|parsed_videos| {
-            println!("Parsing endpoints");
             videos = parsed_videos;
             parse_endpoints(&mut splitted, header.endpoint_count)
         }).and_then(|parsed_endpoints| {
-            println!("Parsing requests");
             endpoints = parsed_endpoints;
             parse_requests(&mut splitted, header.request_count)
-        }).map(|requests| {
-            println!("Done");
-            (CacheInfo::new(header.cache_count, header.cache_capacity), videos, endpoints, requests)
+        }).and_then(|parsed_requests| {
+            requests = parsed_requests;
+            parse_tiers(&mut splitted, header.cache_count)
+        }).and_then(|tiers| {
+            for (cache_id, parent_id, latency) in tiers {
+                cache_info.set_parent(cache_id, parent_id, latency);
+            }
+            parse_throughputs(&mut splitted, header.cache_count)
+        }).filter(|_| {
+            splitted.next().is_none()
+        }).map(|throughputs| {
+            for (cache_id, throughput) in cache_info.ids().zip(throughputs) {
+                cache_info.set_throughput(cache_id, throughput);
+            }
+            (cache_info, videos, endpoints, requests)
+        })
+        .filter(|(cache_info, videos, endpoints, requests)| {
+            ids_in_range(cache_info, videos, endpoints, requests) && is_acyclic(cache_info)
+        })
+}
+
+// Every id refers to a parsed video, endpoint or cache, so that solvers can index by id
+fn ids_in_range(cache_info: &CacheInfo, videos: &[Video], endpoints: &[Endpoint], requests: &[Request]) -> bool {
+    endpoints.iter()
+        .flat_map(|endpoint| endpoint.cache_to_latency.keys())
+        .all(|cache_id| cache_id.0 < cache_info.count)
+        && requests.iter().all(|request| {
+            request.video_id.index() < videos.len() && request.endpoint_id.index() < endpoints.len()
         })
 }
 
@@ -37,7 +78,8 @@ struct Header {
     endpoint_count: i32,
     request_count: i32,
     cache_count: i32,
-    cache_capacity: i32
+    // None when each cache has its own capacity
+    cache_capacity: Option<i32>
 }
 
 impl Header {
@@ -47,7 +89,7 @@ impl Header {
             endpoint_count: 0,
             request_count: 0,
             cache_count: 0,
-            cache_capacity: 0
+            cache_capacity: None
         }
     }
 }
@@ -56,28 +98,44 @@ fn string_to_i32(string: &str) -> Option<i32> {
     string.parse::<i32>().ok()
 }
 
+// Counts, sizes, latencies and ids of an input: out of range values are rejected
+// rather than wrapped
+fn string_to_value(string: &str) -> Option<i32> {
+    string_to_i32(string).filter(|&value| value >= 0)
+}
+
 fn parse_header(header: &str) -> Option<Header> {
-    let parsed_header: Option<Vec<i32>> = header.split(' ').map(string_to_i32).collect();
-    parsed_header.and_then(|values| {
-        match values.len() {
-            5 => Some(Header {
-                video_count: values[0],
-                endpoint_count: values[1],
-                request_count: values[2],
-                cache_count: values[3],
-                cache_capacity: values[4]
-            }),
-            _ => None
-        }
+    let fields: Vec<&str> = header.split(' ').collect();
+    if fields.len() != 5 {
+        return None;
+    }
+    let parsed_header: Option<Vec<i32>> = fields[..4].iter().cloned().map(string_to_value).collect();
+    let cache_capacity = match fields[4] {
+        "*" => None,
+        capacity => Some(string_to_value(capacity)?)
+    };
+    parsed_header.map(|values| Header {
+        video_count: values[0],
+        endpoint_count: values[1],
+        request_count: values[2],
+        cache_count: values[3],
+        cache_capacity
     })
 }
 
+fn parse_capacities(capacities: &str, cache_count: i32) -> Option<CacheInfo> {
+    let parsed_capacities: Option<Vec<i32>> = capacities.split(' ').map(string_to_value).collect();
+    parsed_capacities
+        .filter(|capacities| capacities.len() as i32 == cache_count)
+        .map(CacheInfo::with_capacities)
+}
+
 fn parse_videos(header: &str, video_count: i32) -> Option<Vec<Video>> {
-    let parsed_sizes: Option<Vec<i32>> = header.split(' ').map(string_to_i32).collect();
+    let parsed_sizes: Option<Vec<i32>> = header.split(' ').map(string_to_value).collect();
     parsed_sizes.and_then(|sizes| {
         if sizes.len() as i32 == video_count {
             Some(sizes.iter().enumerate()
-                .map(|(id, &size)| Video::new(id as i32, size))
+                .map(|(id, &size)| Video::new(VideoId(id as i32), size))
                 .collect::<Vec<Video>>())
         } else {
             None
@@ -85,7 +143,7 @@ fn parse_videos(header: &str, video_count: i32) -> Option<Vec<Video>> {
     })
 }
 
-fn parse_endpoints(iter: &mut Iterator<Item = &str>, endpoint_count: i32) -> Option<Vec<Endpoint>> {
+fn parse_endpoints(iter: &mut dyn Iterator<Item = &str>, endpoint_count: i32) -> Option<Vec<Endpoint>> {
     let mut state = CurrentEndpointState::new();
     while !state.error && state.latency_to_cache.len() < endpoint_count as usize {
         if iter.next().map(|input| state.process(input)).is_none() {
@@ -95,7 +153,9 @@ fn parse_endpoints(iter: &mut Iterator<Item = &str>, endpoint_count: i32) -> Opt
 
     if !state.error {
         Some(state.latency_to_cache.into_iter().enumerate()
-            .map(|(id, latency_to_cache)| Endpoint::new(id as i32, latency_to_cache))
+            .map(|(id, (datacenter_latency, latency_to_cache))| {
+                Endpoint::new(EndpointId(id as i32), datacenter_latency, latency_to_cache)
+            })
             .collect::<Vec<Endpoint>>())
     } else {
         None
@@ -103,11 +163,12 @@ fn parse_endpoints(iter: &mut Iterator<Item = &str>, endpoint_count: i32) -> Opt
 }
 
 struct CurrentEndpointState {
-    current_latency_to_cache: BTreeMap<i32, i32>,
+    current_datacenter_latency: i32,
+    current_latency_to_cache: BTreeMap<CacheId, i32>,
     current_total_latency_count: i32,
     current_latency_count: i32,
     shoud_parse_header: bool,
-    latency_to_cache: Vec<BTreeMap<i32, i32>>,
+    latency_to_cache: Vec<(i32, BTreeMap<CacheId, i32>)>,
     error: bool
 
 }
@@ -115,6 +176,7 @@ struct CurrentEndpointState {
 impl CurrentEndpointState {
     fn new() -> CurrentEndpointState {
         CurrentEndpointState {
+            current_datacenter_latency: 0,
             current_latency_to_cache: BTreeMap::new(),
             current_total_latency_count: 0,
             current_latency_count: 0,
@@ -125,7 +187,7 @@ impl CurrentEndpointState {
     }
 
     fn parse_header(&mut self, header: &str) {
-        let values: Option<Vec<i32>> = header.split(' ').map(string_to_i32).collect();
+        let values: Option<Vec<i32>> = header.split(' ').map(string_to_value).collect();
         match values {
             Some(ref values) if values.len() == 2 => self.set_header(values[1], values[0]),
             _ => self.error = true
@@ -133,8 +195,8 @@ impl CurrentEndpointState {
     }
 
     fn set_header(&mut self, total_latency_count: i32, datacenter_latency: i32) {
+        self.current_datacenter_latency = datacenter_latency;
         self.current_latency_to_cache = BTreeMap::new();
-        self.current_latency_to_cache.insert(-1, datacenter_latency);
         self.current_total_latency_count = total_latency_count;
         self.current_latency_count = 0;
         self.shoud_parse_header = false;
@@ -142,14 +204,14 @@ impl CurrentEndpointState {
 
     fn parse_latency(&mut self, latency: &str) {
         self.current_latency_count += 1;
-        let values: Option<Vec<i32>> = latency.split(' ').map(string_to_i32).collect();
+        let values: Option<Vec<i32>> = latency.split(' ').map(string_to_value).collect();
         match values {
-            Some(ref values) if values.len() == 2 => self.set_latency(values[0], values[1]),
+            Some(ref values) if values.len() == 2 => self.set_latency(CacheId(values[0]), values[1]),
             _ => self.error = true
         }
     }
 
-    fn set_latency(&mut self, cache_id: i32, latency: i32) {
+    fn set_latency(&mut self, cache_id: CacheId, latency: i32) {
         self.current_latency_to_cache.insert(cache_id, latency);
     }
 
@@ -162,13 +224,13 @@ impl CurrentEndpointState {
 
         if self.current_latency_count == self.current_total_latency_count {
             self.shoud_parse_header = true;
-            self.latency_to_cache.push(self.current_latency_to_cache.clone());
+            self.latency_to_cache.push((self.current_datacenter_latency, self.current_latency_to_cache.clone()));
         }
     }
 }
 
-fn parse_requests(iter: &mut Iterator<Item = &str>, requests_count: i32) -> Option<Vec<Request>> {
-    let parsed_requests: Option<Vec<Request>> = iter.map(parse_request).collect();
+fn parse_requests(iter: &mut dyn Iterator<Item = &str>, requests_count: i32) -> Option<Vec<Request>> {
+    let parsed_requests: Option<Vec<Request>> = iter.take(requests_count as usize).map(parse_request).collect();
     parsed_requests.and_then(|requests| {
         if requests.len() as i32 == requests_count {
             Some(requests)
@@ -179,12 +241,71 @@ fn parse_requests(iter: &mut Iterator<Item = &str>, requests_count: i32) -> Opti
 }
 
 fn parse_request(request: &str) -> Option<Request> {
-    let parsed_request: Option<Vec<i32>> = request.split(' ').map(string_to_i32).collect();
+    let parsed_request: Option<Vec<i32>> = request.split(' ').map(string_to_value).collect();
     parsed_request.and_then(|request| {
-        if request.len() == 3 {
-            Some(Request::new(request[0], request[1], request[2]))
-        } else {
-            None
+        match request[..] {
+            [video_id, endpoint_id, count] => Some(Request::new(VideoId(video_id), EndpointId(endpoint_id), count)),
+            [video_id, endpoint_id, count, slot] if slot < MAX_SLOTS => {
+                Some(Request::new(VideoId(video_id), EndpointId(endpoint_id), count).with_slot(slot))
+            }
+            _ => None
         }
     })
-}
\ No newline at end of file
+}
+
+// None when a cache is its own parent, has several parents or is out of range
+fn parse_tiers(iter: &mut dyn Iterator<Item = &str>, cache_count: i32) -> Option<Vec<(CacheId, CacheId, i32)>> {
+    let tier_count = match iter.next() {
+        Some(line) => string_to_value(line)?,
+        None => return Some(Vec::new())
+    };
+    let parsed_tiers: Option<Vec<(CacheId, CacheId, i32)>> = iter.take(tier_count as usize).map(|tier| {
+        let values: Vec<i32> = tier.split(' ').map(string_to_value).collect::<Option<Vec<i32>>>()?;
+        match values[..] {
+            [cache_id, parent_id, latency] if cache_id < cache_count && parent_id < cache_count
+                && cache_id != parent_id => Some((CacheId(cache_id), CacheId(parent_id), latency)),
+            _ => None
+        }
+    }).collect();
+    parsed_tiers.filter(|tiers| {
+        let backed: BTreeSet<CacheId> = tiers.iter().map(|&(cache_id, _, _)| cache_id).collect();
+        tiers.len() as i32 == tier_count && backed.len() == tiers.len()
+    })
+}
+
+// Unlimited caches when the line is missing
+fn parse_throughputs(iter: &mut dyn Iterator<Item = &str>, cache_count: i32) -> Option<Vec<Option<i64>>> {
+    let throughputs = match iter.next() {
+        Some(line) => line,
+        None => return Some(vec![None; cache_count as usize])
+    };
+    let parsed_throughputs: Option<Vec<Option<i64>>> = throughputs.split(' ')
+        .map(|throughput| match throughput {
+            "*" => Some(None),
+            throughput => string_to_value(throughput).map(|throughput| Some(throughput as i64))
+        })
+        .collect();
+    parsed_throughputs.filter(|throughputs| throughputs.len() as i32 == cache_count)
+}
+
+// Parses a submission, as written by produce_output
+pub fn parse_output(input: &str) -> Option<BTreeMap<CacheId, BTreeSet<VideoId>>> {
+    let mut splitted = input.trim().split('\n');
+    let cache_count = splitted.next().and_then(|line| string_to_i32(line.trim()))?;
+
+    let mut placement: BTreeMap<CacheId, BTreeSet<VideoId>> = BTreeMap::new();
+    for line in splitted {
+        let values: Vec<i32> = line.split_whitespace().map(string_to_i32).collect::<Option<Vec<i32>>>()?;
+        let (&cache_id, video_ids) = values.split_first()?;
+        let video_ids: BTreeSet<VideoId> = video_ids.iter().map(|&video_id| VideoId(video_id)).collect();
+        if placement.insert(CacheId(cache_id), video_ids).is_some() {
+            return None;
+        }
+    }
+
+    if placement.len() as i32 == cache_count {
+        Some(placement)
+    } else {
+        None
+    }
+}
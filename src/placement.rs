@@ -0,0 +1,249 @@
+use types::*;
+use parser::parse_output;
+use output::produce_output;
+use constraints::Constraints;
+use dense::DenseInstance;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+// Videos stored in each cache of an instance. The placement knows the cache
+// capacities and the video sizes of the instance it was created for, and never
+// lets a cache hold more than its capacity. When created with constraints, it
+// also never holds a forbidden video nor loses a pinned one, and never holds a
+// video in more caches than allowed nor drops it below its minimum.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    capacities: Vec<i32>,
+    video_sizes: Vec<i32>,
+    used: Vec<i64>,
+    caches: Vec<BTreeSet<VideoId>>,
+    // Number of caches holding each video
+    replicas: Vec<i32>,
+    constraints: Arc<Constraints>
+}
+
+impl Placement {
+    // Empty placement
+    pub fn new(cache_info: &CacheInfo, videos: &[Video]) -> Placement {
+        Placement {
+            capacities: cache_info.caches.iter().map(|cache| cache.capacity).collect(),
+            video_sizes: videos.iter().map(|video| video.size).collect(),
+            used: vec![0; cache_info.count as usize],
+            caches: vec![BTreeSet::new(); cache_info.count as usize],
+            replicas: vec![0; videos.len()],
+            constraints: Arc::new(Constraints::default())
+        }
+    }
+
+    pub fn for_instance(instance: &Instance) -> Placement {
+        Placement::new(&instance.cache_info, &instance.videos)
+    }
+
+    // Placement holding only the pinned videos and the minimum number of copies of
+    // each video, failing when the constraints cannot be met. Copies go to the
+    // caches where they save the most, the largest videos being placed first.
+    pub fn with_constraints(instance: &Instance, constraints: &Constraints) -> Result<Placement, String> {
+        constraints.check(instance)?;
+        let mut placement = Placement::for_instance(instance);
+        placement.constraints = Arc::new(constraints.clone());
+        for &(cache_id, video_id) in &constraints.pinned {
+            placement.try_add(cache_id, video_id);
+        }
+        if !constraints.has_replica_limits() {
+            return Ok(placement);
+        }
+
+        let dense = DenseInstance::from_instance(instance);
+        let mut videos: Vec<&Video> = instance.videos.iter().collect();
+        videos.sort_by(|a, b| b.size.cmp(&a.size).then(a.id.cmp(&b.id)));
+        for video in videos {
+            let min = constraints.replica_limits(video.id).min;
+            if placement.replicas(video.id) >= min {
+                continue;
+            }
+            let mut savings: Vec<i64> = vec![0; placement.cache_count()];
+            for &(endpoint_id, requests) in dense.video_demand.row(video.id.index()) {
+                let datacenter_latency = dense.datacenter_latencies[endpoint_id.index()];
+                for &(cache_id, latency) in dense.endpoint_caches.row(endpoint_id.index()) {
+                    savings[cache_id.index()] += (datacenter_latency - latency).max(0) as i64 * requests;
+                }
+            }
+            let mut cache_ids: Vec<CacheId> = instance.cache_info.ids().collect();
+            cache_ids.sort_by_key(|&cache_id| (-savings[cache_id.index()], -placement.free_space(cache_id), cache_id));
+            for cache_id in cache_ids {
+                if placement.replicas(video.id) >= min {
+                    break;
+                }
+                placement.try_add(cache_id, video.id);
+            }
+            if placement.replicas(video.id) < min {
+                return Err(format!("Video {} must be in {} caches but only fits in {}", video.id, min,
+                                   placement.replicas(video.id)));
+            }
+        }
+        Ok(placement)
+    }
+
+    // Same constraints, holding only the pinned videos and the minimum copies
+    pub fn pinned(&self) -> Placement {
+        let mut pinned = self.clone();
+        for (cache_id, video_ids) in self.iter() {
+            for &video_id in video_ids {
+                pinned.remove(cache_id, video_id);
+            }
+        }
+        pinned
+    }
+
+    pub fn constraints(&self) -> &Constraints {
+        &self.constraints
+    }
+
+    // Adds the videos of the other placement that fit
+    pub fn extend(&mut self, other: &Placement) {
+        for (cache_id, video_ids) in other.iter() {
+            for &video_id in video_ids {
+                self.try_add(cache_id, video_id);
+            }
+        }
+    }
+
+    // Fails on unknown caches or videos, and on caches holding more than their capacity
+    pub fn from_map(instance: &Instance, map: &BTreeMap<CacheId, BTreeSet<VideoId>>) -> Result<Placement, String> {
+        let mut placement = Placement::for_instance(instance);
+        for (&cache_id, video_ids) in map {
+            if !placement.has_cache(cache_id) {
+                return Err(format!("Invalid cache {}", cache_id));
+            }
+            if let Some(video_id) = video_ids.iter().find(|&&video_id| !placement.has_video(video_id)) {
+                return Err(format!("Invalid video {} in cache {}", video_id, cache_id));
+            }
+
+            let used: i64 = video_ids.iter().map(|&video_id| placement.size(video_id)).sum();
+            let capacity = placement.capacities[cache_id.index()];
+            if used > capacity as i64 {
+                return Err(format!("Cache {} stores {} but can only hold {}", cache_id, used, capacity));
+            }
+            placement.used[cache_id.index()] = used;
+            placement.caches[cache_id.index()] = video_ids.clone();
+            for video_id in video_ids {
+                placement.replicas[video_id.index()] += 1;
+            }
+        }
+        Ok(placement)
+    }
+
+    // Reads a submission, as written by to_submission
+    pub fn from_submission(instance: &Instance, submission: &str) -> Result<Placement, String> {
+        let map = parse_output(submission).ok_or(String::from("Unable to parse submission"))?;
+        Placement::from_map(instance, &map).map_err(|err| format!("Invalid submission: {}", err))
+    }
+
+    pub fn to_submission(&self) -> String {
+        produce_output(self)
+    }
+
+    pub fn to_map(&self) -> BTreeMap<CacheId, BTreeSet<VideoId>> {
+        self.iter().map(|(cache_id, video_ids)| (cache_id, video_ids.clone())).collect()
+    }
+
+    pub fn cache_count(&self) -> usize {
+        self.caches.len()
+    }
+
+    pub fn video_count(&self) -> usize {
+        self.video_sizes.len()
+    }
+
+    // Number of caches holding the video
+    pub fn replicas(&self, video_id: VideoId) -> i32 {
+        self.replicas[video_id.index()]
+    }
+
+    // Caches by id, empty ones included
+    pub fn iter(&self) -> impl Iterator<Item = (CacheId, &BTreeSet<VideoId>)> {
+        self.caches.iter().enumerate().map(|(index, video_ids)| (CacheId(index as i32), video_ids))
+    }
+
+    pub fn videos(&self, cache_id: CacheId) -> &BTreeSet<VideoId> {
+        &self.caches[cache_id.index()]
+    }
+
+    pub fn contains(&self, cache_id: CacheId, video_id: VideoId) -> bool {
+        self.caches[cache_id.index()].contains(&video_id)
+    }
+
+    pub fn used(&self, cache_id: CacheId) -> i64 {
+        self.used[cache_id.index()]
+    }
+
+    pub fn free_space(&self, cache_id: CacheId) -> i64 {
+        self.capacities[cache_id.index()] as i64 - self.used[cache_id.index()]
+    }
+
+    // Whether the video could be added to the cache
+    pub fn fits(&self, cache_id: CacheId, video_id: VideoId) -> bool {
+        !self.contains(cache_id, video_id) && self.allows(cache_id, video_id)
+            && self.size(video_id) <= self.free_space(cache_id)
+    }
+
+    // Whether the video is not forbidden in the cache, nor already in as many
+    // caches as allowed
+    pub fn allows(&self, cache_id: CacheId, video_id: VideoId) -> bool {
+        !self.constraints.forbidden.contains(&(cache_id, video_id))
+            && self.constraints.replica_limits(video_id).max.is_none_or(|max| self.replicas(video_id) < max)
+    }
+
+    pub fn is_pinned(&self, cache_id: CacheId, video_id: VideoId) -> bool {
+        self.constraints.pinned.contains(&(cache_id, video_id))
+    }
+
+    // Whether the cache holds the video, which is neither pinned there nor at its
+    // minimum number of copies
+    pub fn can_remove(&self, cache_id: CacheId, video_id: VideoId) -> bool {
+        self.contains(cache_id, video_id) && !self.is_pinned(cache_id, video_id)
+            && self.replicas(video_id) > self.constraints.replica_limits(video_id).min
+    }
+
+    // Adds the video if it fits, returns whether it was added
+    pub fn try_add(&mut self, cache_id: CacheId, video_id: VideoId) -> bool {
+        let fits = self.fits(cache_id, video_id);
+        if fits {
+            self.used[cache_id.index()] += self.size(video_id);
+            self.caches[cache_id.index()].insert(video_id);
+            self.replicas[video_id.index()] += 1;
+        }
+        fits
+    }
+
+    // Returns whether the video was in the cache and could be removed, see can_remove
+    pub fn remove(&mut self, cache_id: CacheId, video_id: VideoId) -> bool {
+        let removed = self.can_remove(cache_id, video_id);
+        if removed {
+            self.caches[cache_id.index()].remove(&video_id);
+            self.used[cache_id.index()] -= self.size(video_id);
+            self.replicas[video_id.index()] -= 1;
+        }
+        removed
+    }
+
+    // Whether the placement was created for an instance with these caches and videos
+    pub fn is_for(&self, instance: &Instance) -> bool {
+        self.capacities.iter().eq(instance.cache_info.caches.iter().map(|cache| &cache.capacity))
+            && self.video_sizes.iter().eq(instance.videos.iter().map(|video| &video.size))
+    }
+
+    // Size of the video in the instance
+    pub fn size(&self, video_id: VideoId) -> i64 {
+        self.video_sizes[video_id.index()] as i64
+    }
+
+    fn has_cache(&self, cache_id: CacheId) -> bool {
+        cache_id.0 >= 0 && cache_id.index() < self.caches.len()
+    }
+
+    fn has_video(&self, video_id: VideoId) -> bool {
+        video_id.0 >= 0 && video_id.index() < self.video_sizes.len()
+    }
+}
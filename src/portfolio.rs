@@ -0,0 +1,148 @@
+use types::*;
+use context::Context;
+use progress::Verbosity;
+use algo::{Mode, algo_from};
+use score::{score, validate};
+use search::local_search;
+use placement::Placement;
+use reduce::reduce;
+use std::cmp::max;
+use std::time::{Duration, Instant};
+
+pub struct Run {
+    pub mode: Mode,
+    pub local_search: bool,
+    pub seed: u64,
+    pub valid: bool,
+    pub score: i64,
+    pub duration: Duration,
+    // Largest candidate table built by the strategy and its local search
+    pub peak_candidates: usize,
+    pub placement: Placement
+}
+
+impl Run {
+    pub fn name(&self) -> String {
+        if self.local_search {
+            format!("{}+ls", self.mode.name())
+        } else {
+            String::from(self.mode.name())
+        }
+    }
+}
+
+// Runs every mode on the instance, starting from the initial placement. With
+// local search, each mode is also followed by a local search pass, reported as
+// a separate run. Randomized steps are seeded with the given seed. Each
+// strategy and each local search gets the full time budget of the context,
+// unless the context has limits for it.
+pub fn portfolio(context: &Context, instance: &Instance, initial: &Placement, modes: &[Mode],
+                 with_local_search: bool, seed: u64) -> Vec<Run> {
+    run_modes(context, instance, initial, modes, with_local_search, seed, &|mode, local_search, duration, placement| {
+        evaluate(instance, mode, local_search, seed, duration, placement)
+    })
+}
+
+// Same as portfolio, but the strategies run on the reduced instance. Placements
+// are mapped back, with the constraints of the initial placement, and scored on
+// the original instance. Fails when the pinned videos of the initial placement
+// do not fit in the reduced instance.
+pub fn reduced_portfolio(context: &Context, instance: &Instance, initial: &Placement,
+                         modes: &[Mode], with_local_search: bool, seed: u64) -> Result<Vec<Run>, String> {
+    let reduction = reduce(instance).with_constraints_of(initial)?;
+    context.log(Verbosity::Normal, || reduction.stats.summary(instance));
+
+    let reduced = reduction.to_reduced(initial)?;
+    Ok(run_modes(context, &reduction.instance, &reduced, modes, with_local_search, seed,
+                 &|mode, local_search, duration, placement| {
+        evaluate(instance, mode, local_search, seed, duration, reduction.to_original(&placement))
+    }))
+}
+
+type Evaluate<'a> = dyn Fn(Mode, bool, Duration, Placement) -> Run + 'a;
+
+fn run_modes(context: &Context, instance: &Instance, initial: &Placement, modes: &[Mode],
+             with_local_search: bool, seed: u64, evaluate: &Evaluate) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+    for &mode in modes {
+        if context.is_cancelled() {
+            break;
+        }
+
+        let start = Instant::now();
+        let algo_context = context.for_solver(mode.name());
+        let placement = algo_from(mode, &algo_context, initial, instance.cache_info.clone(),
+                                  instance.videos.clone(), instance.endpoints.clone(), instance.requests.clone());
+        let algo_duration = start.elapsed();
+
+        let mut run = evaluate(mode, false, algo_duration, placement.clone());
+        run.peak_candidates = algo_context.peak_candidates();
+        report(context, &run);
+        runs.push(run);
+        if with_local_search {
+            let start = Instant::now();
+            let search_context = context.for_solver("local-search");
+            let improved = local_search(&search_context, instance, placement, seed);
+            let duration = algo_duration + start.elapsed();
+            let mut run = evaluate(mode, true, duration, improved);
+            run.peak_candidates = max(algo_context.peak_candidates(), search_context.peak_candidates());
+            report(context, &run);
+            runs.push(run);
+        }
+    }
+    runs
+}
+
+fn evaluate(instance: &Instance, mode: Mode, local_search: bool, seed: u64, duration: Duration,
+            placement: Placement) -> Run {
+    let valid = validate(instance, &placement).is_ok();
+    Run {
+        mode,
+        local_search,
+        seed,
+        valid,
+        score: if valid { score(instance, &placement) } else { 0 },
+        duration,
+        peak_candidates: 0,
+        placement
+    }
+}
+
+fn report(context: &Context, run: &Run) {
+    context.log(Verbosity::Normal, || {
+        format!("{}: score {} in {} ms", run.name(), run.score, run.duration.as_millis())
+    });
+}
+
+// Score of the strategy of the run once the replication limits of the initial
+// placement are lifted, so that the cost of the limits can be reported. The
+// strategy starts again from the videos of the initial placement, its pinned
+// and forbidden videos still applying.
+pub fn unlimited_score(context: &Context, instance: &Instance, initial: &Placement, run: &Run)
+                       -> Result<i64, String> {
+    let mut unlimited = Placement::with_constraints(instance, &initial.constraints().without_replica_limits())?;
+    unlimited.extend(initial);
+    Ok(reduced_portfolio(context, instance, &unlimited, &[run.mode], run.local_search, run.seed)?.iter()
+        .filter(|other| other.valid && other.local_search == run.local_search)
+        .map(|other| other.score)
+        .max()
+        .unwrap_or(0))
+}
+
+// The valid run with the highest score, the fastest one winning ties
+pub fn best(runs: &[Run]) -> Option<&Run> {
+    runs.iter()
+        .filter(|run| run.valid)
+        .max_by(|a, b| a.score.cmp(&b.score).then(b.duration.cmp(&a.duration)))
+}
+
+pub fn format_table(runs: &[Run]) -> String {
+    let best_name = best(runs).map(|run| run.name());
+    let header = format!("{:<24} {:>12} {:>12} {:>20}\n", "strategy", "score", "time (ms)", "seed");
+    runs.iter().fold(header, |result, run| {
+        let score = if run.valid { format!("{}", run.score) } else { String::from("invalid") };
+        let marker = if Some(run.name()) == best_name { " *" } else { "" };
+        result + &format!("{:<24} {:>12} {:>12} {:>20}{}\n", run.name(), score, run.duration.as_millis(), run.seed,
+                          marker)
+    })
+}
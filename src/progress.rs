@@ -0,0 +1,192 @@
+use std::io::{stderr, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Silent,
+    Normal,
+    Verbose
+}
+
+pub struct Progress<'a> {
+    pub label: &'a str,
+    pub done: u64,
+    // None when the amount of work is not known in advance
+    pub total: Option<u64>,
+    pub best_score: Option<i64>,
+    pub elapsed: Duration
+}
+
+impl<'a> Progress<'a> {
+    pub fn percent(&self) -> Option<f64> {
+        self.total.map(|total| match total {
+            0 => 100.,
+            _ => self.done as f64 * 100. / total as f64
+        })
+    }
+
+    // Remaining time, extrapolated from the speed so far
+    pub fn eta(&self) -> Option<Duration> {
+        match self.total {
+            Some(total) if self.done > 0 && self.done <= total => {
+                Some(self.elapsed.mul_f64((total - self.done) as f64 / self.done as f64))
+            }
+            _ => None
+        }
+    }
+}
+
+// Receives messages and progress updates from the parser and the solvers
+pub trait Reporter: Send + Sync {
+    fn verbosity(&self) -> Verbosity;
+    fn message(&self, message: &str);
+    fn progress(&self, progress: &Progress);
+}
+
+pub struct SilentReporter;
+
+impl Reporter for SilentReporter {
+    fn verbosity(&self) -> Verbosity {
+        Verbosity::Silent
+    }
+
+    fn message(&self, _: &str) {}
+
+    fn progress(&self, _: &Progress) {}
+}
+
+// Writes to stderr, keeping stdout for results
+pub struct ConsoleReporter {
+    verbosity: Verbosity
+}
+
+impl ConsoleReporter {
+    pub fn new(verbosity: Verbosity) -> ConsoleReporter {
+        ConsoleReporter {
+            verbosity
+        }
+    }
+}
+
+impl Reporter for ConsoleReporter {
+    fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    fn message(&self, message: &str) {
+        let _ = writeln!(stderr(), "{}", message);
+    }
+
+    fn progress(&self, progress: &Progress) {
+        let _ = writeln!(stderr(), "{}", format_progress(progress));
+    }
+}
+
+// Prefixes messages and progress with a label, such as the file being solved
+pub struct PrefixedReporter {
+    prefix: String,
+    inner: Arc<dyn Reporter>
+}
+
+impl PrefixedReporter {
+    pub fn new(prefix: &str, inner: Arc<dyn Reporter>) -> PrefixedReporter {
+        PrefixedReporter {
+            prefix: String::from(prefix),
+            inner
+        }
+    }
+}
+
+impl Reporter for PrefixedReporter {
+    fn verbosity(&self) -> Verbosity {
+        self.inner.verbosity()
+    }
+
+    fn message(&self, message: &str) {
+        self.inner.message(&format!("{}: {}", self.prefix, message));
+    }
+
+    fn progress(&self, progress: &Progress) {
+        let label = format!("{}: {}", self.prefix, progress.label);
+        self.inner.progress(&Progress {
+            label: &label,
+            ..*progress
+        });
+    }
+}
+
+pub fn format_progress(progress: &Progress) -> String {
+    let mut returned = match progress.percent() {
+        Some(percent) => format!("{}: {:.1}%", progress.label, percent),
+        None => format!("{}: {}", progress.label, progress.done)
+    };
+    if let Some(best_score) = progress.best_score {
+        returned += &format!(", best {}", best_score);
+    }
+    if let Some(eta) = progress.eta() {
+        returned += &format!(", ETA {}s", eta.as_secs());
+    }
+    returned
+}
+
+// Rate-limits progress updates of a single task, so that solvers can report
+// after every step without flooding the reporter
+pub struct Tracker<'a> {
+    reporter: &'a dyn Reporter,
+    label: &'a str,
+    total: Option<u64>,
+    interval: Duration,
+    start: Instant,
+    last: Option<Instant>
+}
+
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+impl<'a> Tracker<'a> {
+    pub fn new(reporter: &'a dyn Reporter, label: &'a str, total: Option<u64>) -> Tracker<'a> {
+        Tracker {
+            reporter,
+            label,
+            total,
+            interval: DEFAULT_INTERVAL,
+            start: Instant::now(),
+            last: None
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Tracker<'a> {
+        self.interval = interval;
+        self
+    }
+
+    pub fn update(&mut self, done: u64, best_score: Option<i64>) {
+        if self.reporter.verbosity() == Verbosity::Silent {
+            return;
+        }
+
+        let now = Instant::now();
+        if self.last.is_some_and(|last| now.duration_since(last) < self.interval) {
+            return;
+        }
+        self.emit(now, done, best_score);
+    }
+
+    // Always reports, whatever the time since the last update
+    pub fn finish(&mut self, done: u64, best_score: Option<i64>) {
+        if self.reporter.verbosity() != Verbosity::Silent {
+            self.emit(Instant::now(), done, best_score);
+        }
+    }
+
+    fn emit(&mut self, now: Instant, done: u64, best_score: Option<i64>) {
+        self.last = Some(now);
+        self.reporter.progress(&Progress {
+            label: self.label,
+            done,
+            total: self.total,
+            best_score,
+            elapsed: now.duration_since(self.start)
+        });
+    }
+}
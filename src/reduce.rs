@@ -0,0 +1,217 @@
+use types::*;
+use placement::Placement;
+use constraints::{Constraints, ReplicaLimits};
+use tiers::flattened;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+// Instance without the parts that cannot contribute to the score, with ids
+// remapped to stay contiguous. Placements found on the reduced instance are
+// mapped back to the original ids with to_original.
+pub struct Reduction {
+    pub instance: Instance,
+    pub stats: ReductionStats,
+    // Original id of each reduced video and cache
+    video_ids: Vec<VideoId>,
+    cache_ids: Vec<CacheId>,
+    // Placement of the original instance holding only the pinned videos and the
+    // minimum copies
+    original: Placement
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReductionStats {
+    pub oversized_videos: usize,
+    pub unrequested_videos: usize,
+    pub slow_links: usize,
+    pub disconnected_endpoints: usize,
+    pub unused_caches: usize,
+    pub removed_requests: usize
+}
+
+impl ReductionStats {
+    pub fn summary(&self, original: &Instance) -> String {
+        format!("Removed {} / {} videos ({} too large, {} not requested), {} / {} endpoints, {} / {} caches, \
+                 {} / {} requests and {} slow cache links",
+                self.oversized_videos + self.unrequested_videos, original.videos.len(),
+                self.oversized_videos, self.unrequested_videos,
+                self.disconnected_endpoints, original.endpoints.len(),
+                self.unused_caches, original.cache_info.count,
+                self.removed_requests, original.requests.len(),
+                self.slow_links)
+    }
+}
+
+// Removes videos larger than every cache or never requested, cache links that are
+// not faster than the datacenter, endpoints with no cache left and caches
+// with no endpoint left, along with the requests that cannot be served by a cache.
+// Tiers are flattened first, so caches only reached through their children are kept.
+pub fn reduce(instance: &Instance) -> Reduction {
+    let flat = flattened(instance);
+    let instance: &Instance = &flat;
+    let mut stats = ReductionStats::default();
+
+    // Cache links slower than the datacenter never save anything
+    let mut endpoint_caches: Vec<BTreeMap<CacheId, i32>> = instance.endpoints.iter().map(|endpoint| {
+        let caches: BTreeMap<CacheId, i32> = endpoint.cache_to_latency.iter()
+            .filter(|&(_, &latency)| latency < endpoint.datacenter_latency)
+            .map(|(&cache_id, &latency)| (cache_id, latency))
+            .collect();
+        stats.slow_links += endpoint.cache_to_latency.len() - caches.len();
+        caches
+    }).collect();
+
+    let kept_endpoints: BTreeSet<EndpointId> = instance.endpoints.iter()
+        .filter(|endpoint| !endpoint_caches[endpoint.id.index()].is_empty())
+        .map(|endpoint| endpoint.id)
+        .collect();
+    stats.disconnected_endpoints = instance.endpoints.len() - kept_endpoints.len();
+
+    let oversized: BTreeSet<VideoId> = instance.videos.iter()
+        .filter(|video| video.size > instance.cache_info.max_capacity())
+        .map(|video| video.id)
+        .collect();
+    stats.oversized_videos = oversized.len();
+
+    let kept_requests: Vec<&Request> = instance.requests.iter()
+        .filter(|request| request.count > 0)
+        .filter(|request| kept_endpoints.contains(&request.endpoint_id))
+        .filter(|request| !oversized.contains(&request.video_id))
+        .collect();
+    stats.removed_requests = instance.requests.len() - kept_requests.len();
+
+    let requested: BTreeSet<VideoId> = kept_requests.iter().map(|request| request.video_id).collect();
+    stats.unrequested_videos = instance.videos.len() - oversized.len() - requested.len();
+
+    let used_caches: BTreeSet<CacheId> = kept_endpoints.iter()
+        .flat_map(|&endpoint_id| endpoint_caches[endpoint_id.index()].keys().cloned())
+        .collect();
+    stats.unused_caches = instance.cache_info.count as usize - used_caches.len();
+
+    // Remap every kept id to a contiguous range
+    let video_ids: Vec<VideoId> = requested.into_iter().collect();
+    let cache_ids: Vec<CacheId> = used_caches.into_iter().collect();
+    let endpoint_ids: Vec<EndpointId> = kept_endpoints.into_iter().collect();
+    let new_video_id = reverse(&video_ids, VideoId);
+    let new_cache_id = reverse(&cache_ids, CacheId);
+    let new_endpoint_id = reverse(&endpoint_ids, EndpointId);
+
+    let videos: Vec<Video> = video_ids.iter().enumerate()
+        .map(|(id, &original_id)| Video::new(VideoId(id as i32), instance.videos[original_id.index()].size))
+        .collect();
+
+    let endpoints: Vec<Endpoint> = endpoint_ids.iter().enumerate().map(|(id, &original_id)| {
+        let original = &instance.endpoints[original_id.index()];
+        let cache_to_latency: BTreeMap<CacheId, i32> = std::mem::take(&mut endpoint_caches[original_id.index()])
+            .into_iter()
+            .map(|(cache_id, latency)| (*new_cache_id.get(&cache_id).unwrap(), latency))
+            .collect();
+        Endpoint::new(EndpointId(id as i32), original.datacenter_latency, cache_to_latency)
+    }).collect();
+
+    let requests: Vec<Request> = kept_requests.iter()
+        .map(|request| {
+            let video_id = *new_video_id.get(&request.video_id).unwrap();
+            let endpoint_id = *new_endpoint_id.get(&request.endpoint_id).unwrap();
+            Request::new(video_id, endpoint_id, request.count).with_slot(request.slot)
+        })
+        .collect();
+
+    let mut cache_info = CacheInfo::with_capacities(cache_ids.iter()
+        .map(|&cache_id| instance.cache_info.capacity(cache_id))
+        .collect());
+    for (new_id, &cache_id) in cache_info.ids().zip(&cache_ids) {
+        cache_info.set_throughput(new_id, instance.cache_info.caches[cache_id.index()].throughput);
+    }
+    Reduction {
+        instance: Instance::new(cache_info, videos, endpoints, requests),
+        stats,
+        video_ids,
+        cache_ids,
+        original: Placement::for_instance(instance)
+    }
+}
+
+fn reverse<T: Ord + Copy>(ids: &[T], new_id: fn(i32) -> T) -> BTreeMap<T, T> {
+    ids.iter().enumerate().map(|(index, &id)| (id, new_id(index as i32))).collect()
+}
+
+impl Reduction {
+    // Placements mapped back get the constraints of the placement, and keep its
+    // pinned videos and minimum copies even when their cache or video was removed.
+    // The space these copies take in the kept caches is not available anymore.
+    // Fails when they take more than the capacity of the cache.
+    pub fn with_constraints_of(mut self, placement: &Placement) -> Result<Reduction, String> {
+        self.original = placement.pinned();
+        let new_video_id = reverse(&self.video_ids, VideoId);
+        for (new_id, &cache_id) in self.cache_ids.iter().enumerate() {
+            let taken: i64 = self.original.videos(cache_id).iter()
+                .filter(|video_id| !new_video_id.contains_key(video_id))
+                .map(|&video_id| self.original.size(video_id))
+                .sum();
+            let cache = &mut self.instance.cache_info.caches[new_id];
+            if taken > cache.capacity as i64 {
+                return Err(format!("Videos kept in cache {} take {} but it can only hold {}", cache_id, taken,
+                                   cache.capacity));
+            }
+            cache.capacity -= taken as i32;
+        }
+        Ok(self)
+    }
+
+    pub fn to_original(&self, placement: &Placement) -> Placement {
+        let mut original = self.original.clone();
+        for (cache_id, video_ids) in placement.iter() {
+            for &video_id in video_ids {
+                original.try_add(self.cache_ids[cache_id.index()], self.video_ids[video_id.index()]);
+            }
+        }
+        original
+    }
+
+    // Videos and caches that were removed are dropped, which never lowers the score.
+    // So are the constraints on them. The pinned videos and minimum copies of the
+    // placement are pinned in the reduced one, and the copies kept in removed
+    // caches count towards the maximum number of copies of each video. Fails when
+    // these videos do not fit in the reduced caches.
+    pub fn to_reduced(&self, placement: &Placement) -> Result<Placement, String> {
+        let new_video_id = reverse(&self.video_ids, VideoId);
+        let new_cache_id = reverse(&self.cache_ids, CacheId);
+        let reduced_pairs = |pairs: &BTreeSet<(CacheId, VideoId)>| -> BTreeSet<(CacheId, VideoId)> {
+            pairs.iter()
+                .filter_map(|(cache_id, video_id)| Some((*new_cache_id.get(cache_id)?, *new_video_id.get(video_id)?)))
+                .collect()
+        };
+        let fixed = placement.pinned();
+        let fixed_pairs: BTreeSet<(CacheId, VideoId)> = fixed.iter()
+            .flat_map(|(cache_id, video_ids)| video_ids.iter().map(move |&video_id| (cache_id, video_id)))
+            .collect();
+        let mut constraints = Constraints {
+            pinned: reduced_pairs(&fixed_pairs),
+            forbidden: reduced_pairs(&placement.constraints().forbidden),
+            ..Constraints::default()
+        };
+        if placement.constraints().has_replica_limits() {
+            let mut outside: Vec<i32> = vec![0; placement.video_count()];
+            for &(_, video_id) in fixed_pairs.iter().filter(|(cache_id, _)| !new_cache_id.contains_key(cache_id)) {
+                outside[video_id.index()] += 1;
+            }
+            for (new_id, &video_id) in self.video_ids.iter().enumerate() {
+                let max = placement.constraints().replica_limits(video_id).max.map(|max| max - outside[video_id.index()]);
+                constraints.video_replicas.insert(VideoId(new_id as i32), ReplicaLimits {
+                    min: 0,
+                    max
+                });
+            }
+        }
+        let mut reduced = Placement::with_constraints(&self.instance, &constraints)?;
+        for (cache_id, video_ids) in placement.iter() {
+            if let Some(&cache_id) = new_cache_id.get(&cache_id) {
+                for &video_id in video_ids.iter().filter_map(|video_id| new_video_id.get(video_id)) {
+                    reduced.try_add(cache_id, video_id);
+                }
+            }
+        }
+        Ok(reduced)
+    }
+}
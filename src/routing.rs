@@ -0,0 +1,104 @@
+use types::*;
+use dense::DenseInstance;
+use placement::Placement;
+
+// Requests of an endpoint for a video served by a cache
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    pub endpoint_id: EndpointId,
+    pub video_id: VideoId,
+    pub cache_id: CacheId,
+    pub count: i64
+}
+
+pub struct Routing {
+    // Requests not listed are served by the datacenter
+    pub routes: Vec<Route>,
+    // Total latency saved, in milliseconds
+    pub saved: i64
+}
+
+impl Routing {
+    // Requests served by a cache
+    pub fn served(&self) -> i64 {
+        self.routes.iter().map(|route| route.count).sum()
+    }
+
+    // Requests served by the cache
+    pub fn load(&self, cache_id: CacheId) -> i64 {
+        self.routes.iter().filter(|route| route.cache_id == cache_id).map(|route| route.count).sum()
+    }
+}
+
+// Assigns the requests of each endpoint for each video to the caches holding
+// the video, without exceeding their throughput. Requests of a same endpoint
+// and video may be split between caches. Greedy: the largest savings per
+// request are served first, which is optimal without throughput limits but
+// not always with them.
+pub fn route(instance: &Instance, placement: &Placement) -> Routing {
+    let dense = DenseInstance::from_instance(instance);
+    Router::new(&dense).route(&dense, instance, placement)
+}
+
+// The ways of serving each demand from a cache faster than the datacenter,
+// sorted once so that the placements of an instance are routed repeatedly
+// without sorting again
+pub struct Router {
+    // (saving per request, position of the demand in video_demand, cache, video),
+    // the largest savings first
+    options: Vec<(i32, usize, CacheId, VideoId)>
+}
+
+impl Router {
+    pub fn new(dense: &DenseInstance) -> Router {
+        let mut options: Vec<(i32, usize, CacheId, VideoId)> = Vec::new();
+        for video_index in 0..dense.video_demand.row_count() {
+            let video_id = VideoId(video_index as i32);
+            let offset = dense.video_demand.offset(video_index);
+            for (index, &(endpoint_id, _)) in dense.video_demand.row(video_index).iter().enumerate() {
+                let datacenter_latency = dense.datacenter_latencies[endpoint_id.index()];
+                for &(cache_id, latency) in dense.endpoint_caches.row(endpoint_id.index()) {
+                    if latency < datacenter_latency {
+                        options.push((datacenter_latency - latency, offset + index, cache_id, video_id));
+                    }
+                }
+            }
+        }
+        options.sort_by(|a, b| b.0.cmp(&a.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+        Router {
+            options
+        }
+    }
+
+    // Same as route, dense being the instance the router was built for
+    pub fn route(&self, dense: &DenseInstance, instance: &Instance, placement: &Placement) -> Routing {
+        let mut remaining: Vec<i64> = dense.video_demand.items().iter().map(|&(_, count)| count).collect();
+        let mut throughputs: Vec<i64> = instance.cache_info.caches.iter()
+            .map(|cache| cache.throughput.unwrap_or(i64::MAX))
+            .collect();
+
+        let mut routing = Routing {
+            routes: Vec::new(),
+            saved: 0
+        };
+        for &(saving, position, cache_id, video_id) in &self.options {
+            if !placement.contains(cache_id, video_id) {
+                continue;
+            }
+            let count = remaining[position].min(throughputs[cache_id.index()]);
+            if count == 0 {
+                continue;
+            }
+            remaining[position] -= count;
+            throughputs[cache_id.index()] -= count;
+            routing.saved += saving as i64 * count;
+            routing.routes.push(Route {
+                endpoint_id: dense.video_demand.items()[position].0,
+                video_id,
+                cache_id,
+                count
+            });
+        }
+        routing
+    }
+}
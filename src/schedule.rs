@@ -0,0 +1,105 @@
+use types::*;
+use algo::{Mode, algo_from};
+use context::Context;
+use placement::Placement;
+use progress::Verbosity;
+use score::saved;
+use search::local_search;
+
+// Value of the placement of a time slot
+#[derive(Debug, PartialEq)]
+pub struct SlotValue {
+    // Latency saved on the requests of the slot, in milliseconds
+    pub saved: i64,
+    // Size of the videos copied into the caches since the previous slot
+    pub copied: i64
+}
+
+// Size of the videos stored by the next placement but not by the previous one, summed over the caches
+pub fn copied_bytes(previous: &Placement, next: &Placement) -> i64 {
+    next.iter()
+        .flat_map(|(cache_id, video_ids)| {
+            video_ids.iter().filter(move |&&video_id| !previous.contains(cache_id, video_id))
+        })
+        .map(|&video_id| next.size(video_id))
+        .sum()
+}
+
+// One value per placement, the placements being those of consecutive slots. Filling
+// the caches for the first slot is free.
+pub fn slot_values(instance: &Instance, placements: &[Placement]) -> Vec<SlotValue> {
+    placements.iter().enumerate()
+        .map(|(slot, placement)| SlotValue {
+            saved: saved(&instance.for_slot(slot), placement),
+            copied: if slot == 0 { 0 } else { copied_bytes(&placements[slot - 1], placement) }
+        })
+        .collect()
+}
+
+// Latency saved over every slot, minus the migration cost of each unit of video
+// size copied between consecutive slots
+pub fn schedule_score(instance: &Instance, placements: &[Placement], migration_cost: i64) -> i64 {
+    slot_values(instance, placements).iter().map(|value| value.saved - migration_cost * value.copied).sum()
+}
+
+// One placement per slot, solved in order with the mode, followed by a local
+// search seeded with the given seed if asked. Each slot keeps the placement of
+// the previous one as is, completes it, or starts from scratch, whichever saves
+// the most once the copied videos are paid for. Starting from scratch means
+// starting from the pinned videos of the initial placement, whose constraints
+// every slot follows. Every solver run gets the limits of its solver.
+pub fn plan(context: &Context, instance: &Instance, initial: &Placement, mode: Mode, with_local_search: bool,
+            seed: u64, migration_cost: i64) -> Vec<Placement> {
+    let empty = initial.pinned();
+    let mut placements: Vec<Placement> = Vec::new();
+    for slot in 0..instance.slot_count() {
+        if context.is_cancelled() {
+            break;
+        }
+        let slot_instance = instance.for_slot(slot);
+        let solve = |initial: &Placement| {
+            let placement = algo_from(mode, &context.for_solver(mode.name()), initial,
+                                      slot_instance.cache_info.clone(), slot_instance.videos.clone(),
+                                      slot_instance.endpoints.clone(), slot_instance.requests.clone());
+            if with_local_search {
+                local_search(&context.for_solver("local-search"), &slot_instance, placement, seed)
+            } else {
+                placement
+            }
+        };
+
+        let placement = match placements.last() {
+            None => solve(&empty),
+            Some(previous) => {
+                let value = |placement: &Placement| {
+                    saved(&slot_instance, placement) - migration_cost * copied_bytes(previous, placement)
+                };
+                let mut best = previous.clone();
+                let mut best_value = value(&best);
+                for candidate in [solve(previous), solve(&empty)] {
+                    let candidate_value = value(&candidate);
+                    if candidate_value > best_value {
+                        best = candidate;
+                        best_value = candidate_value;
+                    }
+                }
+                best
+            }
+        };
+        context.log(Verbosity::Verbose, || {
+            format!("Slot {}: {} ms saved", slot, saved(&slot_instance, &placement))
+        });
+        placements.push(placement);
+    }
+    placements
+}
+
+pub fn format_schedule(values: &[SlotValue], migration_cost: i64) -> String {
+    let header = format!("{:<8} {:>16} {:>12} {:>16}\n", "slot", "saved (ms)", "copied", "net");
+    let table = values.iter().enumerate().fold(header, |result, (slot, value)| {
+        result + &format!("{:<8} {:>16} {:>12} {:>16}\n", slot, value.saved, value.copied,
+                          value.saved - migration_cost * value.copied)
+    });
+    let total: i64 = values.iter().map(|value| value.saved - migration_cost * value.copied).sum();
+    table + &format!("Schedule score: {}\n", total)
+}
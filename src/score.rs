@@ -0,0 +1,109 @@
+use types::*;
+use placement::Placement;
+use tiers::flattened;
+use routing::route;
+
+// Hash Code score: time saved per request, in microseconds, summed over all requests
+pub fn score(instance: &Instance, placement: &Placement) -> i64 {
+    normalize(instance, saved(instance, placement))
+}
+
+pub fn total_requests(instance: &Instance) -> i64 {
+    instance.requests.iter().map(|request| request.count as i64).sum()
+}
+
+// Converts a total latency saved, in milliseconds, into a score
+pub fn normalize(instance: &Instance, saved: i64) -> i64 {
+    match total_requests(instance) {
+        0 => 0,
+        total_requests => saved * 1000 / total_requests
+    }
+}
+
+// Total latency saved by the placement, in milliseconds. With throughput limits,
+// requests are routed to the caches first.
+pub fn saved(instance: &Instance, placement: &Placement) -> i64 {
+    if instance.cache_info.has_throughput_limits() {
+        return route(instance, placement).saved;
+    }
+    let instance = flattened(instance);
+    let mut saved: i64 = 0;
+    for request in &instance.requests {
+        let endpoint = &instance.endpoints[request.endpoint_id.index()];
+        let best_latency = best_cache_latency(endpoint, request.video_id, placement)
+            .unwrap_or(endpoint.datacenter_latency);
+        saved += (endpoint.datacenter_latency - best_latency).max(0) as i64 * request.count as i64;
+    }
+    saved
+}
+
+// Latency of the fastest cache holding the video for the endpoint, None if no cache holds it.
+// Caches backing the ones of the endpoint must have been flattened.
+fn best_cache_latency(endpoint: &Endpoint, video_id: VideoId, placement: &Placement) -> Option<i32> {
+    endpoint.cache_to_latency.iter()
+        .filter(|&(&cache_id, _)| placement.contains(cache_id, video_id))
+        .map(|(_, &latency)| latency)
+        .min()
+}
+
+// Summary of a valid placement
+#[derive(Debug, PartialEq)]
+pub struct Evaluation {
+    pub score: i64,
+    pub caches_used: usize,
+    pub bytes_stored: i64,
+    pub requests_served: i64,
+    pub total_requests: i64
+}
+
+impl Evaluation {
+    // Fraction of the requests served by a cache rather than by the datacenter
+    pub fn served_ratio(&self) -> f64 {
+        match self.total_requests {
+            0 => 0.,
+            total_requests => self.requests_served as f64 / total_requests as f64
+        }
+    }
+}
+
+pub fn evaluate(instance: &Instance, placement: &Placement) -> Evaluation {
+    let flat = flattened(instance);
+    let requests_served: i64 = if instance.cache_info.has_throughput_limits() {
+        route(instance, placement).served()
+    } else {
+        flat.requests.iter()
+            .filter(|request| {
+                best_cache_latency(&flat.endpoints[request.endpoint_id.index()], request.video_id, placement).is_some()
+            })
+            .map(|request| request.count as i64)
+            .sum()
+    };
+    Evaluation {
+        score: score(instance, placement),
+        caches_used: placement.iter().filter(|(_, video_ids)| !video_ids.is_empty()).count(),
+        bytes_stored: placement.iter().map(|(cache_id, _)| placement.used(cache_id)).sum(),
+        requests_served,
+        total_requests: total_requests(instance)
+    }
+}
+
+pub fn format_evaluation(instance: &Instance, evaluation: &Evaluation) -> String {
+    format!("Score: {}\n\
+             Caches used: {} / {}\n\
+             Bytes stored: {} / {}\n\
+             Requests served from a cache: {} / {} ({:.2}%)\n",
+            evaluation.score,
+            evaluation.caches_used, instance.cache_info.count,
+            evaluation.bytes_stored, instance.cache_info.total_capacity(),
+            evaluation.requests_served, evaluation.total_requests, evaluation.served_ratio() * 100.)
+}
+
+// Placements never exceed the cache capacities, but may have been built for another
+// instance. Their constraints are checked too.
+pub fn validate(instance: &Instance, placement: &Placement) -> Result<(), String> {
+    if placement.is_for(instance) {
+        placement.constraints().check_placement(placement)
+    } else {
+        Err(String::from("Placement built for another instance"))
+    }
+}
@@ -0,0 +1,228 @@
+use types::*;
+use dense::DenseInstance;
+use placement::Placement;
+use rng::Rng;
+use routing::Router;
+use context::Context;
+use score::{saved, normalize};
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::cmp::{max, min};
+
+// Hill climbing on a valid placement: for each cache, add the videos that save
+// the most latency, swapping out the least useful video when the cache is full.
+// Every move strictly improves the score, so the result is never worse than the input.
+// Gains ignore throughput limits, so with limits the moves of a cache visit are
+// undone when the routed score drops.
+// Caches are visited in a random order drawn from the seed. Each cache visit
+// counts as one iteration of the context.
+pub fn local_search(context: &Context, instance: &Instance, placement: Placement, seed: u64) -> Placement {
+    let mut rng = Rng::new(seed);
+    let mut state = SearchState::new(instance, placement);
+    let mut current_saved = saved(instance, &state.placement);
+    // Routes placements without sorting the options again at every cache visit
+    let router = if instance.cache_info.has_throughput_limits() { Some(Router::new(&state.dense)) } else { None };
+    let mut tracker = context.tracker("local search", None);
+    let mut cache_ids: Vec<CacheId> = instance.cache_info.ids().collect();
+    let mut iteration: u64 = 0;
+    let mut improved = true;
+    while improved && !context.should_stop(iteration) {
+        improved = false;
+        rng.shuffle(&mut cache_ids);
+        for &cache_id in &cache_ids {
+            if context.should_stop(iteration) {
+                break;
+            }
+            let before = state.placement.videos(cache_id).clone();
+            let mut gained = state.improve_cache(context, iteration, cache_id);
+            if let Some(router) = router.as_ref().filter(|_| gained > 0) {
+                gained = router.route(&state.dense, instance, &state.placement).saved - current_saved;
+                if gained <= 0 {
+                    state.restore(cache_id, &before);
+                    gained = 0;
+                }
+            }
+            improved |= gained > 0;
+            current_saved += gained;
+            iteration += 1;
+            tracker.update(iteration, Some(normalize(instance, current_saved)));
+        }
+    }
+    tracker.finish(iteration, Some(normalize(instance, current_saved)));
+    state.placement
+}
+
+struct SearchState<'a> {
+    instance: &'a Instance,
+    dense: DenseInstance,
+    // For each requested (endpoint, video), at its position in dense.endpoint_demand,
+    // the best latency currently achieved
+    best_latency: Vec<i32>,
+    cache_count: usize,
+    // Whether each video is in each cache, at video * cache_count + cache
+    stored: Vec<bool>,
+    placement: Placement
+}
+
+impl<'a> SearchState<'a> {
+    fn new(instance: &'a Instance, placement: Placement) -> SearchState<'a> {
+        let dense = DenseInstance::from_instance(instance);
+        let cache_count = instance.cache_info.count as usize;
+        let mut stored: Vec<bool> = vec![false; instance.videos.len() * cache_count];
+        for (cache_id, video_ids) in placement.iter() {
+            for video_id in video_ids {
+                stored[video_id.index() * cache_count + cache_id.index()] = true;
+            }
+        }
+
+        let mut state = SearchState {
+            instance,
+            dense,
+            best_latency: Vec::new(),
+            cache_count,
+            stored,
+            placement
+        };
+        state.best_latency = instance.endpoints.iter().flat_map(|endpoint| {
+            state.dense.endpoint_demand.row(endpoint.id.index()).iter()
+                .map(|&(video_id, _)| state.compute_best_latency(endpoint.id, video_id, None))
+                .collect::<Vec<i32>>()
+        }).collect();
+        state
+    }
+
+    fn is_stored(&self, cache_id: CacheId, video_id: VideoId) -> bool {
+        self.stored[video_id.index() * self.cache_count + cache_id.index()]
+    }
+
+    fn set_stored(&mut self, cache_id: CacheId, video_id: VideoId, stored: bool) {
+        self.stored[video_id.index() * self.cache_count + cache_id.index()] = stored;
+    }
+
+    // Best latency for an endpoint to get a video, ignoring the given cache
+    fn compute_best_latency(&self, endpoint_id: EndpointId, video_id: VideoId, ignored_cache_id: Option<CacheId>) -> i32 {
+        self.dense.endpoint_caches.row(endpoint_id.index()).iter()
+            .filter(|&&(cache_id, _)| Some(cache_id) != ignored_cache_id)
+            .filter(|&&(cache_id, _)| self.is_stored(cache_id, video_id))
+            .fold(self.dense.datacenter_latencies[endpoint_id.index()], |best, &(_, latency)| min(best, latency))
+    }
+
+    // Latency saved by adding each requested video to the cache, indexed by video
+    fn add_gains(&self, cache_id: CacheId) -> Vec<i64> {
+        let mut gains: Vec<i64> = vec![0; self.instance.videos.len()];
+        for &(endpoint_id, latency) in self.dense.cache_endpoints.row(cache_id.index()) {
+            let offset = self.dense.endpoint_demand.offset(endpoint_id.index());
+            for (index, &(video_id, _)) in self.dense.endpoint_demand.row(endpoint_id.index()).iter().enumerate() {
+                if !self.is_stored(cache_id, video_id) {
+                    let requests = self.dense.endpoint_requests(offset + index);
+                    gains[video_id.index()] += max(self.best_latency[offset + index] - latency, 0) as i64 * requests;
+                }
+            }
+        }
+        gains
+    }
+
+    // Latency lost by removing a video from the cache
+    fn remove_loss(&self, cache_id: CacheId, video_id: VideoId) -> i64 {
+        self.dense.cache_endpoints.row(cache_id.index()).iter()
+            .filter_map(|&(endpoint_id, latency)| {
+                self.dense.demand_position(endpoint_id, video_id).map(|position| (endpoint_id, latency, position))
+            })
+            .filter(|&(_, latency, position)| self.best_latency[position] == latency)
+            .map(|(endpoint_id, latency, position)| {
+                let requests = self.dense.endpoint_requests(position);
+                (self.compute_best_latency(endpoint_id, video_id, Some(cache_id)) - latency) as i64 * requests
+            })
+            .sum()
+    }
+
+    fn update_best_latencies(&mut self, cache_id: CacheId, video_id: VideoId) {
+        let updated: Vec<(usize, i32)> = self.dense.cache_endpoints.row(cache_id.index()).iter()
+            .filter_map(|&(endpoint_id, _)| {
+                self.dense.demand_position(endpoint_id, video_id)
+                    .map(|position| (position, self.compute_best_latency(endpoint_id, video_id, None)))
+            })
+            .collect();
+        for (position, best_latency) in updated {
+            self.best_latency[position] = best_latency;
+        }
+    }
+
+    fn add(&mut self, cache_id: CacheId, video_id: VideoId) {
+        self.placement.try_add(cache_id, video_id);
+        self.set_stored(cache_id, video_id, true);
+        self.update_best_latencies(cache_id, video_id);
+    }
+
+    fn remove(&mut self, cache_id: CacheId, video_id: VideoId) {
+        self.placement.remove(cache_id, video_id);
+        self.set_stored(cache_id, video_id, false);
+        self.update_best_latencies(cache_id, video_id);
+    }
+
+    // Puts back the videos the cache held
+    fn restore(&mut self, cache_id: CacheId, video_ids: &BTreeSet<VideoId>) {
+        let added: Vec<VideoId> = self.placement.videos(cache_id).difference(video_ids).cloned().collect();
+        for video_id in added {
+            self.remove(cache_id, video_id);
+        }
+        for &video_id in video_ids {
+            if !self.placement.contains(cache_id, video_id) {
+                self.add(cache_id, video_id);
+            }
+        }
+    }
+
+    // Returns the latency saved by the moves done in the cache
+    fn improve_cache(&mut self, context: &Context, iteration: u64, cache_id: CacheId) -> i64 {
+        let mut candidates: Vec<(i64, VideoId)> = self.add_gains(cache_id).into_iter().enumerate()
+            .filter(|&(_, gain)| gain > 0)
+            .map(|(video_id, gain)| (gain, VideoId(video_id as i32)))
+            .collect();
+        candidates.sort_by(|a, b| b.cmp(a));
+        context.record_candidates(candidates.len());
+
+        // Pinned videos and videos at their minimum number of copies are never swapped out
+        let mut losses: BTreeMap<VideoId, i64> = self.placement.videos(cache_id).iter()
+            .filter(|&&video_id| self.placement.can_remove(cache_id, video_id))
+            .map(|&video_id| (video_id, self.remove_loss(cache_id, video_id)))
+            .collect();
+
+        let mut gained: i64 = 0;
+        for (gain, video_id) in candidates {
+            if context.should_stop(iteration) {
+                break;
+            }
+
+            let size = self.instance.videos[video_id.index()].size as i64;
+            if size > self.instance.cache_info.capacity(cache_id) as i64 || !self.placement.allows(cache_id, video_id) {
+                continue;
+            }
+
+            let free_space = self.placement.free_space(cache_id);
+            if self.placement.fits(cache_id, video_id) {
+                self.add(cache_id, video_id);
+                losses.insert(video_id, gain);
+                gained += gain;
+                continue;
+            }
+
+            // Swap with the video that costs the least to remove while making enough room
+            let swapped = losses.iter()
+                .filter(|&(&other_id, _)| self.instance.videos[other_id.index()].size as i64 + free_space >= size)
+                .filter(|&(&other_id, _)| self.placement.can_remove(cache_id, other_id))
+                .min_by_key(|&(&other_id, &loss)| (loss, other_id))
+                .map(|(&other_id, &loss)| (other_id, loss));
+            if let Some((other_id, loss)) = swapped {
+                if gain > loss {
+                    self.remove(cache_id, other_id);
+                    losses.remove(&other_id);
+                    self.add(cache_id, video_id);
+                    losses.insert(video_id, gain);
+                    gained += gain - loss;
+                }
+            }
+        }
+        gained
+    }
+}
@@ -0,0 +1,163 @@
+use types::*;
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq)]
+pub struct Distribution {
+    pub count: usize,
+    pub min: i64,
+    pub max: i64,
+    pub mean: f64,
+    pub median: i64,
+    pub p90: i64,
+    pub total: i64
+}
+
+impl Distribution {
+    pub fn new(mut values: Vec<i64>) -> Distribution {
+        values.sort();
+        let total: i64 = values.iter().sum();
+        Distribution {
+            count: values.len(),
+            min: values.first().cloned().unwrap_or(0),
+            max: values.last().cloned().unwrap_or(0),
+            mean: match values.len() {
+                0 => 0.,
+                count => total as f64 / count as f64
+            },
+            median: percentile(&values, 50),
+            p90: percentile(&values, 90),
+            total
+        }
+    }
+}
+
+// Nearest-rank percentile of sorted values
+fn percentile(values: &[i64], percent: usize) -> i64 {
+    match values.len() {
+        0 => 0,
+        count => values[((count * percent).div_ceil(100)).max(1) - 1]
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InstanceStats {
+    pub videos: usize,
+    pub endpoints: usize,
+    pub caches: i32,
+    pub cache_capacity: Distribution,
+    pub request_descriptions: usize,
+    pub total_demand: i64,
+    pub video_size: Distribution,
+    pub oversized_videos: usize,
+    pub unrequested_videos: usize,
+    pub requests_per_video: Distribution,
+    // Share of all requests going to the most popular 1% and 10% of the videos
+    pub top_1_percent_share: f64,
+    pub top_10_percent_share: f64,
+    pub endpoints_per_cache: Distribution,
+    pub caches_per_endpoint: Distribution,
+    pub datacenter_latency: Distribution,
+    // Latency saved by each endpoint to cache link
+    pub latency_saving: Distribution
+}
+
+pub fn stats(instance: &Instance) -> InstanceStats {
+    let mut requests_per_video: Vec<i64> = vec![0; instance.videos.len()];
+    for request in &instance.requests {
+        requests_per_video[request.video_id.index()] += request.count as i64;
+    }
+
+    let mut endpoints_per_cache: BTreeMap<CacheId, i64> = instance.cache_info.ids().map(|id| (id, 0)).collect();
+    let mut latency_saving: Vec<i64> = Vec::new();
+    for endpoint in &instance.endpoints {
+        for (cache_id, &latency) in &endpoint.cache_to_latency {
+            *endpoints_per_cache.entry(*cache_id).or_insert(0) += 1;
+            latency_saving.push((endpoint.datacenter_latency - latency) as i64);
+        }
+    }
+
+    let mut popularity = requests_per_video.clone();
+    popularity.sort_by(|a, b| b.cmp(a));
+    let total_demand: i64 = popularity.iter().sum();
+    let top_share = |percent: usize| match total_demand {
+        0 => 0.,
+        _ => {
+            let top = (popularity.len() * percent).div_ceil(100);
+            popularity.iter().take(top).sum::<i64>() as f64 / total_demand as f64
+        }
+    };
+
+    InstanceStats {
+        videos: instance.videos.len(),
+        endpoints: instance.endpoints.len(),
+        caches: instance.cache_info.count,
+        cache_capacity: Distribution::new(instance.cache_info.caches.iter()
+            .map(|cache| cache.capacity as i64)
+            .collect()),
+        request_descriptions: instance.requests.len(),
+        total_demand,
+        video_size: Distribution::new(instance.videos.iter().map(|video| video.size as i64).collect()),
+        oversized_videos: instance.videos.iter().filter(|video| video.size > instance.cache_info.max_capacity())
+            .count(),
+        unrequested_videos: requests_per_video.iter().filter(|&&requests| requests == 0).count(),
+        top_1_percent_share: top_share(1),
+        top_10_percent_share: top_share(10),
+        requests_per_video: Distribution::new(requests_per_video),
+        endpoints_per_cache: Distribution::new(endpoints_per_cache.values().cloned().collect()),
+        caches_per_endpoint: Distribution::new(instance.endpoints.iter()
+            .map(|endpoint| endpoint.cache_to_latency.len() as i64)
+            .collect()),
+        datacenter_latency: Distribution::new(instance.endpoints.iter()
+            .map(|endpoint| endpoint.datacenter_latency as i64)
+            .collect()),
+        latency_saving: Distribution::new(latency_saving)
+    }
+}
+
+fn distributions(stats: &InstanceStats) -> Vec<(&'static str, &Distribution)> {
+    vec![("cache_capacity", &stats.cache_capacity),
+         ("video_size", &stats.video_size),
+         ("requests_per_video", &stats.requests_per_video),
+         ("endpoints_per_cache", &stats.endpoints_per_cache),
+         ("caches_per_endpoint", &stats.caches_per_endpoint),
+         ("datacenter_latency", &stats.datacenter_latency),
+         ("latency_saving", &stats.latency_saving)]
+}
+
+pub fn format_report(stats: &InstanceStats) -> String {
+    let capacity = match (stats.cache_capacity.min, stats.cache_capacity.max) {
+        (min, max) if min == max => format!("{}", min),
+        (min, max) => format!("{} to {}", min, max)
+    };
+    let header = format!("Videos: {} ({} larger than every cache, {} never requested)\n\
+                          Endpoints: {}\n\
+                          Caches: {} of capacity {}\n\
+                          Requests: {} descriptions, {} in total\n\
+                          Top 1% videos: {:.1}% of the requests\n\
+                          Top 10% videos: {:.1}% of the requests\n\n",
+                         stats.videos, stats.oversized_videos, stats.unrequested_videos,
+                         stats.endpoints,
+                         stats.caches, capacity,
+                         stats.request_descriptions, stats.total_demand,
+                         stats.top_1_percent_share * 100., stats.top_10_percent_share * 100.);
+    let table = format!("{:<22} {:>10} {:>10} {:>12} {:>10} {:>10}\n", "", "min", "median", "mean", "p90", "max");
+    distributions(stats).iter().fold(header + &table, |result, &(name, distribution)| {
+        result + &format!("{:<22} {:>10} {:>10} {:>12.1} {:>10} {:>10}\n", name, distribution.min,
+                          distribution.median, distribution.mean, distribution.p90, distribution.max)
+    })
+}
+
+// One key=value pair per line, for scripts
+pub fn format_key_values(stats: &InstanceStats) -> String {
+    let returned = format!("videos={}\nendpoints={}\ncaches={}\nrequest_descriptions={}\n\
+                            total_demand={}\noversized_videos={}\nunrequested_videos={}\n\
+                            top_1_percent_share={:.4}\ntop_10_percent_share={:.4}\n",
+                           stats.videos, stats.endpoints, stats.caches,
+                           stats.request_descriptions, stats.total_demand, stats.oversized_videos,
+                           stats.unrequested_videos, stats.top_1_percent_share, stats.top_10_percent_share);
+    distributions(stats).iter().fold(returned, |result, &(name, distribution)| {
+        result + &format!("{0}.min={1}\n{0}.median={2}\n{0}.mean={3:.2}\n{0}.p90={4}\n{0}.max={5}\n{0}.total={6}\n",
+                          name, distribution.min, distribution.median, distribution.mean, distribution.p90,
+                          distribution.max, distribution.total)
+    })
+}
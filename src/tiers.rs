@@ -0,0 +1,66 @@
+use types::*;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+// Caches may be backed by a parent cache, itself maybe backed by another one. An
+// endpoint gets a video from any cache on the way from one of its caches up to
+// the last parent, the fastest path winning, and from the datacenter otherwise.
+
+// Latency from the endpoint to every cache it reaches, directly or through the
+// parents of its caches
+pub fn effective_latencies(cache_info: &CacheInfo, endpoint: &Endpoint) -> BTreeMap<CacheId, i32> {
+    let mut latencies: BTreeMap<CacheId, i32> = BTreeMap::new();
+    for (&cache_id, &latency) in &endpoint.cache_to_latency {
+        let mut current = Some((cache_id, latency));
+        while let Some((cache_id, latency)) = current {
+            // Its parents were reached at least as fast through it
+            if latencies.get(&cache_id).is_some_and(|&best| best <= latency) {
+                break;
+            }
+            latencies.insert(cache_id, latency);
+            current = cache_info.caches[cache_id.index()].parent
+                .map(|(parent_id, parent_latency)| (parent_id, latency.saturating_add(parent_latency)));
+        }
+    }
+    latencies
+}
+
+// Same instance without parents, each endpoint being linked to every cache it
+// reaches at its effective latency, so that flat solvers and scoring apply
+pub fn flatten(instance: &Instance) -> Instance {
+    let mut cache_info = instance.cache_info.clone();
+    for cache in &mut cache_info.caches {
+        cache.parent = None;
+    }
+    let endpoints: Vec<Endpoint> = instance.endpoints.iter()
+        .map(|endpoint| {
+            Endpoint::new(endpoint.id, endpoint.datacenter_latency, effective_latencies(&instance.cache_info, endpoint))
+        })
+        .collect();
+    Instance::new(cache_info, instance.videos.clone(), endpoints, instance.requests.clone())
+}
+
+// Flattens the instance only when it has tiers
+pub fn flattened(instance: &Instance) -> Cow<'_, Instance> {
+    if instance.cache_info.has_tiers() {
+        Cow::Owned(flatten(instance))
+    } else {
+        Cow::Borrowed(instance)
+    }
+}
+
+// Whether following parents from any cache ends at the datacenter
+pub fn is_acyclic(cache_info: &CacheInfo) -> bool {
+    cache_info.caches.iter().all(|cache| {
+        let mut current = cache.parent;
+        let mut steps = 0;
+        while let Some((parent_id, _)) = current {
+            steps += 1;
+            if steps > cache_info.count {
+                return false;
+            }
+            current = cache_info.caches[parent_id.index()].parent;
+        }
+        true
+    })
+}
@@ -1,64 +1,211 @@
 use std::collections::BTreeMap;
+use std::fmt;
 
+// Identifiers are distinct types, so that a video id cannot be used as a cache id
+macro_rules! id_type {
+    ($name:ident) => {
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub i32);
+
+        impl $name {
+            // Position in the vectors indexed by this id
+            pub fn index(self) -> usize {
+                self.0 as usize
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "{}", self.0)
+            }
+        }
+    }
+}
+
+id_type!(VideoId);
+id_type!(CacheId);
+id_type!(EndpointId);
+
+#[derive(Clone)]
 pub struct Video {
-    pub id: i32,
+    pub id: VideoId,
     pub size: i32
 }
 
 impl Video {
-    pub fn new(id: i32, size: i32) -> Video {
+    pub fn new(id: VideoId, size: i32) -> Video {
         Video {
-            id: id,
-            size: size
+            id,
+            size
         }
     }
 }
 
+#[derive(Clone)]
 pub struct Endpoint {
-    pub id: i32,
-    pub cache_to_latency: BTreeMap<i32, i32>
+    pub id: EndpointId,
+    pub datacenter_latency: i32,
+    // Only the caches connected to the endpoint
+    pub cache_to_latency: BTreeMap<CacheId, i32>
 }
 
 impl Endpoint {
-    pub fn new(id: i32, cache_to_latency: BTreeMap<i32, i32>) -> Endpoint {
+    pub fn new(id: EndpointId, datacenter_latency: i32, cache_to_latency: BTreeMap<CacheId, i32>) -> Endpoint {
         Endpoint {
-            id: id,
-            cache_to_latency: cache_to_latency
+            id,
+            datacenter_latency,
+            cache_to_latency
         }
     }
 }
 
+#[derive(Clone)]
 pub struct Cache {
-    pub id: i32,
-    pub capacity: i32
+    pub id: CacheId,
+    pub capacity: i32,
+    // Cache backing this one and the latency to reach it, None when only the
+    // datacenter backs it
+    pub parent: Option<(CacheId, i32)>,
+    // Number of requests the cache can serve, None when unlimited
+    pub throughput: Option<i64>
+}
+
+impl Cache {
+    pub fn new(id: CacheId, capacity: i32) -> Cache {
+        Cache {
+            id,
+            capacity,
+            parent: None,
+            throughput: None
+        }
+    }
 }
 
+// Time slots are numbered from 0, up to the hours of a week
+pub const MAX_SLOTS: i32 = 168;
+
+#[derive(Clone)]
 pub struct Request {
-    pub video_id: i32,
-    pub endpoint_id: i32,
-    pub count: i32
+    pub video_id: VideoId,
+    pub endpoint_id: EndpointId,
+    pub count: i32,
+    // Time slot of the requests, 0 when the instance is not sliced
+    pub slot: i32
 }
 
 impl Request {
-    pub fn new(video_id: i32, endpoint_id: i32, count: i32) -> Request {
+    pub fn new(video_id: VideoId, endpoint_id: EndpointId, count: i32) -> Request {
         Request {
-            video_id: video_id,
-            endpoint_id: endpoint_id,
-            count: count
+            video_id,
+            endpoint_id,
+            count,
+            slot: 0
         }
     }
+
+    pub fn with_slot(mut self, slot: i32) -> Request {
+        self.slot = slot;
+        self
+    }
 }
 
+// Caches are indexed by id
+#[derive(Clone)]
 pub struct CacheInfo {
     pub count: i32,
-    pub capacity: i32
+    pub caches: Vec<Cache>
 }
 
 impl CacheInfo {
+    // Caches of the same capacity, as in the standard input format
     pub fn new(count: i32, capacity: i32) -> CacheInfo {
+        CacheInfo::with_capacities((0..count).map(|_| capacity).collect())
+    }
+
+    pub fn with_capacities(capacities: Vec<i32>) -> CacheInfo {
         CacheInfo {
-            count: count,
-            capacity: capacity
+            count: capacities.len() as i32,
+            caches: capacities.into_iter().enumerate()
+                .map(|(id, capacity)| Cache::new(CacheId(id as i32), capacity))
+                .collect()
         }
     }
-}
\ No newline at end of file
+
+    pub fn ids(&self) -> impl Iterator<Item = CacheId> {
+        (0..self.count).map(CacheId)
+    }
+
+    pub fn set_parent(&mut self, cache_id: CacheId, parent_id: CacheId, latency: i32) {
+        self.caches[cache_id.index()].parent = Some((parent_id, latency));
+    }
+
+    // Whether some cache is backed by another one
+    pub fn has_tiers(&self) -> bool {
+        self.caches.iter().any(|cache| cache.parent.is_some())
+    }
+
+    pub fn set_throughput(&mut self, cache_id: CacheId, throughput: Option<i64>) {
+        self.caches[cache_id.index()].throughput = throughput;
+    }
+
+    // Whether some cache can only serve a limited number of requests
+    pub fn has_throughput_limits(&self) -> bool {
+        self.caches.iter().any(|cache| cache.throughput.is_some())
+    }
+
+    pub fn capacity(&self, cache_id: CacheId) -> i32 {
+        self.caches[cache_id.index()].capacity
+    }
+
+    // None when caches have different capacities
+    pub fn uniform_capacity(&self) -> Option<i32> {
+        match self.caches.first() {
+            Some(first) if self.caches.iter().all(|cache| cache.capacity == first.capacity) => Some(first.capacity),
+            Some(_) => None,
+            None => Some(0)
+        }
+    }
+
+    pub fn max_capacity(&self) -> i32 {
+        self.caches.iter().map(|cache| cache.capacity).max().unwrap_or(0)
+    }
+
+    pub fn total_capacity(&self) -> i64 {
+        self.caches.iter().map(|cache| cache.capacity as i64).sum()
+    }
+}
+
+#[derive(Clone)]
+pub struct Instance {
+    pub cache_info: CacheInfo,
+    pub videos: Vec<Video>,
+    pub endpoints: Vec<Endpoint>,
+    pub requests: Vec<Request>
+}
+
+impl Instance {
+    pub fn new(cache_info: CacheInfo, videos: Vec<Video>, endpoints: Vec<Endpoint>,
+               requests: Vec<Request>) -> Instance {
+        Instance {
+            cache_info,
+            videos,
+            endpoints,
+            requests
+        }
+    }
+
+    // Number of time slots, 1 when the instance is not sliced
+    pub fn slot_count(&self) -> usize {
+        self.requests.iter().map(|request| request.slot as usize + 1).max().unwrap_or(1)
+    }
+
+    // Same instance with only the requests of the slot
+    pub fn for_slot(&self, slot: usize) -> Instance {
+        Instance {
+            cache_info: self.cache_info.clone(),
+            videos: self.videos.clone(),
+            endpoints: self.endpoints.clone(),
+            requests: self.requests.iter().filter(|request| request.slot as usize == slot).cloned().collect()
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// Minimal zip archive writer: entries are stored without compression, which
+// every unzip tool reads. Entries are dated 1980-01-01 so that archives of the
+// same files are identical.
+pub struct ZipWriter {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: usize
+}
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const VERSION: u16 = 10;
+// MS-DOS date of 1980-01-01, time is midnight
+const DATE: u16 = (1 << 5) | 1;
+
+impl ZipWriter {
+    pub fn new() -> ZipWriter {
+        ZipWriter {
+            data: Vec::new(),
+            central_directory: Vec::new(),
+            entries: 0
+        }
+    }
+
+    pub fn add(&mut self, name: &str, contents: &[u8]) -> Result<(), String> {
+        if name.len() > u16::MAX as usize || self.entries == u16::MAX as usize {
+            return Err(format!("Too many entries to add {}", name));
+        }
+        let offset = self.data.len();
+        if offset > u32::MAX as usize || contents.len() > u32::MAX as usize {
+            return Err(format!("Archive too large to add {}", name));
+        }
+
+        // Fields shared by the local and central headers
+        let mut common: Vec<u8> = Vec::new();
+        push_u16(&mut common, VERSION);
+        push_u16(&mut common, 0);
+        push_u16(&mut common, 0);
+        push_u16(&mut common, 0);
+        push_u16(&mut common, DATE);
+        push_u32(&mut common, crc32(contents));
+        push_u32(&mut common, contents.len() as u32);
+        push_u32(&mut common, contents.len() as u32);
+        push_u16(&mut common, name.len() as u16);
+        push_u16(&mut common, 0);
+
+        push_u32(&mut self.data, LOCAL_HEADER);
+        self.data.extend_from_slice(&common);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(contents);
+
+        push_u32(&mut self.central_directory, CENTRAL_HEADER);
+        push_u16(&mut self.central_directory, VERSION);
+        self.central_directory.extend_from_slice(&common);
+        push_u16(&mut self.central_directory, 0);
+        push_u16(&mut self.central_directory, 0);
+        push_u16(&mut self.central_directory, 0);
+        push_u32(&mut self.central_directory, 0);
+        push_u32(&mut self.central_directory, offset as u32);
+        self.central_directory.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<Vec<u8>, String> {
+        let offset = self.data.len();
+        if offset + self.central_directory.len() > u32::MAX as usize {
+            return Err(String::from("Archive too large"));
+        }
+        let size = self.central_directory.len();
+        self.data.append(&mut self.central_directory);
+        push_u32(&mut self.data, END_OF_CENTRAL_DIRECTORY);
+        push_u16(&mut self.data, 0);
+        push_u16(&mut self.data, 0);
+        push_u16(&mut self.data, self.entries as u16);
+        push_u16(&mut self.data, self.entries as u16);
+        push_u32(&mut self.data, size as u32);
+        push_u32(&mut self.data, offset as u32);
+        push_u16(&mut self.data, 0);
+        Ok(self.data)
+    }
+}
+
+impl Default for ZipWriter {
+    fn default() -> ZipWriter {
+        ZipWriter::new()
+    }
+}
+
+fn push_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+// CRC-32 as used by zip, computed bit by bit
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 == 1 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 }
+        })
+    })
+}
@@ -1,49 +1,123 @@
 extern crate videos;
 use std::collections::BTreeMap;
 use videos::types::*;
-use videos::algo::{descent_gain, GainMode};
+use videos::context::Context;
+use videos::algo::{algo, descent_gain, GainMode, Mode};
+use videos::score::score;
 
 #[test]
 fn test_gain_1() {
     let cache_info = CacheInfo::new(1, 500);
-    let videos = vec![Video::new(0, 100)];
-    let mut endpoint0_relation: BTreeMap<i32, i32> = BTreeMap::new();
-    endpoint0_relation.insert(-1, 200);
-    endpoint0_relation.insert(0, 100);
-    let endpoints = vec![Endpoint::new(0, endpoint0_relation)];
-    let requests = vec![Request::new(0, 0, 1000)];
-    let gain = descent_gain(GainMode::PureGain, &cache_info, &videos, endpoints, requests);
-    assert_eq!(gain.get(&100000).unwrap()[0], (0, 0));
+    let videos = vec![Video::new(VideoId(0), 100)];
+    let mut endpoint0_relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+    endpoint0_relation.insert(CacheId(0), 100);
+    let endpoints = vec![Endpoint::new(EndpointId(0), 200, endpoint0_relation)];
+    let requests = vec![Request::new(VideoId(0), EndpointId(0), 1000)];
+    let gain = descent_gain(GainMode::PureGain, &Context::new(), &cache_info, &videos, endpoints, requests);
+    assert_eq!(gain.get(&100000).unwrap()[0], (VideoId(0), CacheId(0)));
 }
 
 #[test]
 fn test_gain_2() {
     let cache_info = CacheInfo::new(1, 500);
-    let videos = vec![Video::new(0, 100), Video::new(1, 200)];
-    let mut endpoint0_relation: BTreeMap<i32, i32> = BTreeMap::new();
-    endpoint0_relation.insert(-1, 200);
-    endpoint0_relation.insert(0, 100);
-    let endpoints = vec![Endpoint::new(0, endpoint0_relation)];
-    let requests = vec![Request::new(0, 0, 1000), Request::new(1, 0, 1500)];
-    let gain = descent_gain(GainMode::PureGain, &cache_info, &videos, endpoints, requests);
+    let videos = vec![Video::new(VideoId(0), 100), Video::new(VideoId(1), 200)];
+    let mut endpoint0_relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+    endpoint0_relation.insert(CacheId(0), 100);
+    let endpoints = vec![Endpoint::new(EndpointId(0), 200, endpoint0_relation)];
+    let requests = vec![Request::new(VideoId(0), EndpointId(0), 1000), Request::new(VideoId(1), EndpointId(0), 1500)];
+    let gain = descent_gain(GainMode::PureGain, &Context::new(), &cache_info, &videos, endpoints, requests);
 
-    assert_eq!(gain.get(&100000).unwrap()[0], (0, 0));
-    assert_eq!(gain.get(&150000).unwrap()[0], (1, 0));
+    assert_eq!(gain.get(&100000).unwrap()[0], (VideoId(0), CacheId(0)));
+    assert_eq!(gain.get(&150000).unwrap()[0], (VideoId(1), CacheId(0)));
 }
 
 #[test]
 fn test_gain_3() {
     let cache_info = CacheInfo::new(1, 500);
-    let videos = vec![Video::new(0, 100)];
-    let mut endpoint0_relation: BTreeMap<i32, i32> = BTreeMap::new();
-    endpoint0_relation.insert(-1, 300);
-    endpoint0_relation.insert(0, 200);
-    let mut endpoint1_relation: BTreeMap<i32, i32> = BTreeMap::new();
-    endpoint1_relation.insert(-1, 300);
-    endpoint1_relation.insert(0, 100);
-    let endpoints = vec![Endpoint::new(0, endpoint0_relation), Endpoint::new(1, endpoint1_relation)];
-    let requests = vec![Request::new(0, 0, 1000), Request::new(0, 1, 1500)];
-    let gain = descent_gain(GainMode::PureGain, &cache_info, &videos, endpoints, requests);
-
-    assert_eq!(gain.get(&300000).unwrap()[0], (0, 0));
-}
\ No newline at end of file
+    let videos = vec![Video::new(VideoId(0), 100)];
+    let mut endpoint0_relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+    endpoint0_relation.insert(CacheId(0), 200);
+    let mut endpoint1_relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+    endpoint1_relation.insert(CacheId(0), 100);
+    let endpoints = vec![Endpoint::new(EndpointId(0), 300, endpoint0_relation), Endpoint::new(EndpointId(1), 300, endpoint1_relation)];
+    let requests = vec![Request::new(VideoId(0), EndpointId(0), 1000), Request::new(VideoId(0), EndpointId(1), 1500)];
+    let gain = descent_gain(GainMode::PureGain, &Context::new(), &cache_info, &videos, endpoints, requests);
+
+    assert_eq!(gain.get(&400000).unwrap()[0], (VideoId(0), CacheId(0)));
+}
+
+#[test]
+fn test_descent_amend_iteration_limit() {
+    let cache_info = CacheInfo::new(1, 500);
+    let videos = vec![Video::new(VideoId(0), 100), Video::new(VideoId(1), 200)];
+    let mut endpoint0_relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+    endpoint0_relation.insert(CacheId(0), 100);
+    let endpoints = vec![Endpoint::new(EndpointId(0), 200, endpoint0_relation)];
+    let requests = vec![Request::new(VideoId(0), EndpointId(0), 1000), Request::new(VideoId(1), EndpointId(0), 1500)];
+
+    let context = Context::new().with_max_iterations(1);
+    let placement = algo(Mode::DescentAmend, &context, cache_info.clone(), videos.clone(), endpoints.clone(),
+                         requests.clone());
+    assert_eq!(1, placement.videos(CacheId(0)).len());
+    assert!(placement.contains(CacheId(0), VideoId(1)));
+
+    let placement = algo(Mode::DescentAmend, &Context::new(), cache_info, videos, endpoints, requests);
+    assert_eq!(2, placement.videos(CacheId(0)).len());
+}
+
+// Gains far above i32::MAX: 1000 endpoints each saving 3999 ms on 10000 requests
+fn large_instance() -> (CacheInfo, Vec<Video>, Vec<Endpoint>, Vec<Request>) {
+    let videos = vec![Video::new(VideoId(0), 100)];
+    let endpoints: Vec<Endpoint> = (0..1000).map(|id| {
+        let mut relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+        relation.insert(CacheId(0), 1);
+        Endpoint::new(EndpointId(id), 4000, relation)
+    }).collect();
+    let requests: Vec<Request> = (0..1000).map(|id| Request::new(VideoId(0), EndpointId(id), 10000)).collect();
+    (CacheInfo::new(1, 500), videos, endpoints, requests)
+}
+
+#[test]
+fn test_gain_large_values() {
+    let (cache_info, videos, endpoints, requests) = large_instance();
+    let gain = descent_gain(GainMode::PureGain, &Context::new(), &cache_info, &videos, endpoints, requests);
+    assert_eq!(gain.get(&39_990_000_000).unwrap()[0], (VideoId(0), CacheId(0)));
+}
+
+#[test]
+fn test_descent_amend_large_values() {
+    let (cache_info, videos, endpoints, requests) = large_instance();
+    let placement = algo(Mode::DescentAmend, &Context::new(), cache_info, videos, endpoints, requests);
+    assert!(placement.contains(CacheId(0), VideoId(0)));
+}
+
+// A cache close to the endpoint serving only 500 requests, and a slower one
+// without limit, each holding a single video
+fn throughput_instance() -> Instance {
+    let mut cache_info = CacheInfo::new(2, 100);
+    cache_info.set_throughput(CacheId(0), Some(500));
+    let videos = vec![Video::new(VideoId(0), 100), Video::new(VideoId(1), 100)];
+    let mut relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+    relation.insert(CacheId(0), 100);
+    relation.insert(CacheId(1), 200);
+    let endpoints = vec![Endpoint::new(EndpointId(0), 1000, relation)];
+    let requests = vec![Request::new(VideoId(0), EndpointId(0), 1000), Request::new(VideoId(1), EndpointId(0), 500)];
+    Instance::new(cache_info, videos, endpoints, requests)
+}
+
+#[test]
+fn test_descent_throughput() {
+    let instance = throughput_instance();
+    let placement = algo(Mode::DescentAmend, &Context::new(), instance.cache_info.clone(), instance.videos.clone(),
+                         instance.endpoints.clone(), instance.requests.clone());
+    assert!(placement.contains(CacheId(0), VideoId(0)));
+    // (500 * 900 + 500 * 800) * 1000 / 1500
+    assert_eq!(566666, score(&instance, &placement));
+
+    let placement = algo(Mode::DescentThroughput, &Context::new(), instance.cache_info.clone(),
+                         instance.videos.clone(), instance.endpoints.clone(), instance.requests.clone());
+    assert!(placement.contains(CacheId(0), VideoId(1)));
+    assert!(placement.contains(CacheId(1), VideoId(0)));
+    // (500 * 900 + 1000 * 800) * 1000 / 1500
+    assert_eq!(833333, score(&instance, &placement));
+}
@@ -0,0 +1,46 @@
+extern crate videos;
+use std::sync::Mutex;
+use std::thread;
+use std::collections::HashSet;
+use videos::batch::*;
+
+fn inputs(count: usize) -> Vec<String> {
+    (0..count).map(|index| format!("{}.in", index)).collect()
+}
+
+#[test]
+fn test_run_batch_keeps_order() {
+    let threads = Mutex::new(HashSet::new());
+    let results = run_batch(&inputs(20), 4, |input| {
+        threads.lock().unwrap().insert(thread::current().id());
+        input.len()
+    });
+    assert_eq!(inputs(20).iter().map(|input| input.len()).collect::<Vec<usize>>(), results);
+    assert!(threads.lock().unwrap().len() <= 4);
+}
+
+#[test]
+fn test_run_batch_edge_cases() {
+    assert!(run_batch(&inputs(0), 4, |input| String::from(input)).is_empty());
+    assert_eq!(inputs(3), run_batch(&inputs(3), 0, |input| String::from(input)));
+    assert_eq!(inputs(3), run_batch(&inputs(3), 16, |input| String::from(input)));
+}
+
+#[test]
+fn test_prefix_lines() {
+    assert_eq!("a.in: one\na.in: two\n", prefix_lines("a.in", "one\ntwo\n"));
+    assert_eq!("", prefix_lines("a.in", ""));
+}
+
+#[test]
+fn test_format_summary() {
+    let summary = format_summary(&[
+        BatchResult { file: String::from("a.in"), result: Ok(100) },
+        BatchResult { file: String::from("b.in"), result: Err(String::from("Unable to parse input")) },
+        BatchResult { file: String::from("c.in"), result: Ok(20) }
+    ]);
+    let lines: Vec<&str> = summary.lines().collect();
+    assert_eq!(5, lines.len());
+    assert!(lines[2].starts_with("b.in") && lines[2].contains("failed (Unable to parse input)"));
+    assert_eq!("2 succeeded, 1 failed, total score 120", lines[4]);
+}
@@ -0,0 +1,49 @@
+extern crate videos;
+use std::time::Duration;
+use videos::types::CacheInfo;
+use videos::algo::Mode;
+use videos::placement::Placement;
+use videos::portfolio::Run;
+use videos::bench::{BenchResult, list_inputs, format_matrix};
+
+fn run(mode: Mode, score: i64, millis: u64, peak_candidates: usize) -> Run {
+    Run {
+        mode,
+        local_search: false,
+        seed: 0,
+        valid: true,
+        score,
+        duration: Duration::from_millis(millis),
+        peak_candidates,
+        placement: Placement::new(&CacheInfo::new(0, 0), &[])
+    }
+}
+
+#[test]
+fn test_list_inputs() {
+    assert_eq!(vec![String::from("resources/me_at_the_zoo.in"), String::from("resources/trending_today.in"),
+                    String::from("resources/videos_worth_spreading.in")], list_inputs("resources").unwrap());
+    assert!(list_inputs("does_not_exist").is_err());
+}
+
+#[test]
+fn test_format_matrix() {
+    let results = vec![
+        BenchResult {
+            file: String::from("resources/a.in"),
+            runs: vec![run(Mode::Descent, 100, 5, 10), run(Mode::BestVideo, 50, 1, 2)]
+        },
+        BenchResult {
+            file: String::from("resources/b.in"),
+            runs: vec![run(Mode::Descent, 200, 7, 30), run(Mode::BestVideo, 70, 2, 4)]
+        }
+    ];
+    let matrix = format_matrix(&results);
+    let lines: Vec<Vec<&str>> = matrix.lines().map(|line| line.split_whitespace().collect()).collect();
+    assert_eq!(vec!["Score", "descent", "best-video"], lines[0]);
+    assert_eq!(vec!["a.in", "100", "50"], lines[1]);
+    assert_eq!(vec!["b.in", "200", "70"], lines[2]);
+    assert_eq!(vec!["total", "300", "120"], lines[3]);
+    assert_eq!(vec!["total", "12", "3"], lines[8]);
+    assert_eq!(vec!["total", "40", "6"], lines[13]);
+}
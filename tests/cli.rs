@@ -0,0 +1,209 @@
+extern crate videos;
+use std::fs;
+use std::io::Write;
+use std::process::{Command as Process, Stdio};
+use std::time::Duration;
+use videos::algo::Mode;
+use videos::progress::Verbosity;
+use videos::parser::parse_output;
+use videos::cli::{Command, SolveOptions, ScoreOptions, StatsOptions, PackageOptions, PlanOptions, parse_args,
+                  slot_output_path};
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| String::from(*arg)).collect()
+}
+
+fn solve_options(arguments: &[&str]) -> SolveOptions {
+    match parse_args(&args(arguments)) {
+        Ok(Command::Solve(options)) => options,
+        other => panic!("Unexpected result {:?}", other)
+    }
+}
+
+#[test]
+fn test_defaults() {
+    let options = solve_options(&["resources/me_at_the_zoo.in"]);
+    assert_eq!(vec![String::from("resources/me_at_the_zoo.in")], options.inputs);
+    assert_eq!(Mode::all(), options.modes);
+    assert_eq!(0, options.seed);
+    assert_eq!(None, options.time_limit);
+    assert_eq!(None, options.jobs);
+    assert_eq!(None, options.config);
+    assert!(options.command_line.is_empty());
+    assert!(options.local_search);
+    assert!(options.replication_report);
+    assert_eq!(Verbosity::Normal, options.verbosity);
+    assert_eq!("output/me_at_the_zoo.in.out", options.output_for("resources/me_at_the_zoo.in"));
+    assert_eq!(options, solve_options(&["solve", "resources/me_at_the_zoo.in"]));
+}
+
+#[test]
+fn test_options() {
+    let options = solve_options(&["solve", "-s", "descent", "--solver", "descent-amend", "--seed", "42",
+                                  "-t", "1.5", "-j", "4", "--no-local-search", "-d", "out/", "-q", "a.in", "b.in"]);
+    assert_eq!(vec![Mode::Descent, Mode::DescentAmend], options.modes);
+    assert_eq!(42, options.seed);
+    assert_eq!(Some(Duration::from_millis(1500)), options.time_limit);
+    assert_eq!(Some(4), options.jobs);
+    assert_eq!(vec!["jobs", "local_search", "seed", "solvers", "time_limit"],
+               options.command_line.iter().cloned().collect::<Vec<&str>>());
+    assert!(!options.local_search);
+    assert_eq!(Verbosity::Silent, options.verbosity);
+    assert_eq!("out/b.in.out", options.output_for("b.in"));
+
+    let options = solve_options(&["-o", "x.out", "--warm-start", "y.out", "a.in"]);
+    assert_eq!("x.out", options.output_for("a.in"));
+    assert_eq!(Some(String::from("y.out")), options.warm_start);
+    assert_eq!(Some(String::from("a.conf")), solve_options(&["-c", "a.conf", "a.in"]).config);
+    assert_eq!(Some(String::from("a.constraints")), solve_options(&["--constraints", "a.constraints", "a.in"]).constraints);
+    assert!(!solve_options(&["--no-replication-report", "a.in"]).replication_report);
+
+    let options = solve_options(&["-"]);
+    assert_eq!("-", options.output_for("-"));
+    assert_eq!("x.out", solve_options(&["-o", "x.out", "-"]).output_for("-"));
+    assert_eq!("-", solve_options(&["-o", "-", "a.in"]).output_for("a.in"));
+}
+
+#[test]
+fn test_help() {
+    assert_eq!(Ok(Command::Help), parse_args(&args(&["help"])));
+    assert_eq!(Ok(Command::Help), parse_args(&args(&["--help"])));
+    assert_eq!(Ok(Command::Help), parse_args(&args(&["solve", "a.in", "-h"])));
+}
+
+#[test]
+fn test_errors() {
+    assert!(parse_args(&args(&[])).is_err());
+    assert!(parse_args(&args(&["solve"])).is_err());
+    assert!(parse_args(&args(&["--unknown", "a.in"])).is_err());
+    assert!(parse_args(&args(&["-s", "nope", "a.in"])).is_err());
+    assert!(parse_args(&args(&["a.in", "--seed"])).is_err());
+    assert!(parse_args(&args(&["--seed", "x", "a.in"])).is_err());
+    assert!(parse_args(&args(&["-t", "-1", "a.in"])).is_err());
+    assert!(parse_args(&args(&["-j", "0", "a.in"])).is_err());
+    assert!(parse_args(&args(&["-o", "x.out", "a.in", "b.in"])).is_err());
+    assert!(parse_args(&args(&["-", "a.in"])).is_err());
+    assert!(parse_args(&args(&["--warm-start", "-", "-"])).is_err());
+    assert!(parse_args(&args(&["--constraints", "-", "-"])).is_err());
+}
+
+#[test]
+fn test_score_command() {
+    assert_eq!(Ok(Command::Score(ScoreOptions {
+        input: String::from("resources/me_at_the_zoo.in"),
+        submission: String::from("output/me_at_the_zoo.in.out"),
+        constraints: None
+    })), parse_args(&args(&["score", "resources/me_at_the_zoo.in", "output/me_at_the_zoo.in.out"])));
+    assert_eq!(Ok(Command::Score(ScoreOptions {
+        input: String::from("a.in"),
+        submission: String::from("a.out"),
+        constraints: Some(String::from("a.constraints"))
+    })), parse_args(&args(&["score", "a.in", "--constraints", "a.constraints", "a.out"])));
+    assert_eq!(Ok(Command::Help), parse_args(&args(&["score", "--help"])));
+    assert!(parse_args(&args(&["score", "a.in"])).is_err());
+    assert!(parse_args(&args(&["score", "a.in", "b.out", "c.out"])).is_err());
+    assert!(parse_args(&args(&["score", "-q", "a.in", "b.out"])).is_err());
+    assert!(parse_args(&args(&["score", "-", "-"])).is_err());
+    assert!(parse_args(&args(&["score", "--constraints", "-", "-", "a.out"])).is_err());
+    assert!(parse_args(&args(&["score", "a.in", "a.out", "--constraints"])).is_err());
+}
+
+#[test]
+fn test_stats_command() {
+    assert_eq!(Ok(Command::Stats(StatsOptions {
+        inputs: vec![String::from("a.in"), String::from("b.in")],
+        key_value: true
+    })), parse_args(&args(&["stats", "a.in", "--key-value", "b.in"])));
+    assert!(parse_args(&args(&["stats"])).is_err());
+    assert!(parse_args(&args(&["stats", "--json", "a.in"])).is_err());
+    assert!(parse_args(&args(&["stats", "-", "-"])).is_err());
+}
+
+#[test]
+fn test_bench_command() {
+    match parse_args(&args(&["bench", "-s", "descent", "--no-local-search"])) {
+        Ok(Command::Bench(options)) => {
+            assert_eq!(vec![String::from("resources")], options.inputs);
+            assert_eq!(vec![Mode::Descent], options.modes);
+            assert!(!options.local_search);
+        }
+        other => panic!("Unexpected result {:?}", other)
+    }
+    assert_eq!(Ok(Command::Help), parse_args(&args(&["bench", "--help"])));
+    assert!(parse_args(&args(&["bench", "-o", "x.out"])).is_err());
+    assert!(parse_args(&args(&["bench", "-j", "2"])).is_err());
+}
+
+#[test]
+fn test_pipe() {
+    let mut child = Process::new(env!("CARGO_BIN_EXE_videos"))
+        .args(["solve", "-s", "cache-filling", "--no-local-search", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&fs::read("resources/me_at_the_zoo.in").unwrap()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    // Only the submission goes to stdout
+    let submission = String::from_utf8(output.stdout).unwrap();
+    let caches: Option<usize> = submission.lines().next().and_then(|line| line.parse().ok());
+    assert_eq!(Some(parse_output(&submission).unwrap().len()), caches);
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Best strategy: cache-filling"));
+}
+
+#[test]
+fn test_package_command() {
+    assert_eq!(Ok(Command::Package(PackageOptions {
+        inputs: vec![String::from("resources")],
+        output_dirs: vec![String::from("output")],
+        destination: String::from("submission"),
+        source: String::from(env!("CARGO_MANIFEST_DIR"))
+    })), parse_args(&args(&["package"])));
+    assert_eq!(Ok(Command::Package(PackageOptions {
+        inputs: vec![String::from("a.in")],
+        output_dirs: vec![String::from("x"), String::from("y")],
+        destination: String::from("z"),
+        source: String::from("src")
+    })), parse_args(&args(&["package", "-d", "x", "a.in", "--output-dir", "y", "-o", "z", "--source", "src"])));
+    assert!(matches!(parse_args(&args(&["package", "-"])), Ok(Command::Package(ref options)) if options.inputs == ["-"]));
+    assert!(parse_args(&args(&["package", "-s", "descent"])).is_err());
+}
+
+#[test]
+fn test_plan_command() {
+    let mut options = SolveOptions::new();
+    options.inputs = vec![String::from("a.in")];
+    options.modes = vec![Mode::DescentAmend];
+    assert_eq!(Ok(Command::Plan(PlanOptions {
+        solve: options,
+        migration_cost: 0
+    })), parse_args(&args(&["plan", "a.in"])));
+    match parse_args(&args(&["plan", "-s", "descent", "--migration-cost", "20", "-d", "slots", "-q", "--constraints",
+                             "a.constraints", "--seed", "3", "-t", "1.5", "-c", "a.conf", "--no-local-search", "a.in"])) {
+        Ok(Command::Plan(options)) => {
+            assert_eq!(20, options.migration_cost);
+            assert_eq!(vec![String::from("a.in")], options.solve.inputs);
+            assert_eq!(vec![Mode::Descent], options.solve.modes);
+            assert_eq!("slots", options.solve.output_dir);
+            assert_eq!(Verbosity::Silent, options.solve.verbosity);
+            assert_eq!(Some(String::from("a.constraints")), options.solve.constraints);
+            assert_eq!(3, options.solve.seed);
+            assert_eq!(Some(Duration::from_millis(1500)), options.solve.time_limit);
+            assert_eq!(Some(String::from("a.conf")), options.solve.config);
+            assert!(!options.solve.local_search);
+        }
+        other => panic!("Unexpected result {:?}", other)
+    }
+    assert!(parse_args(&args(&["plan"])).is_err());
+    assert!(parse_args(&args(&["plan", "a.in", "b.in"])).is_err());
+    assert!(parse_args(&args(&["plan", "--migration-cost", "-1", "a.in"])).is_err());
+    assert!(parse_args(&args(&["plan", "--constraints", "-", "-"])).is_err());
+    assert!(parse_args(&args(&["plan", "-s", "descent", "-s", "best-video", "a.in"])).is_err());
+    assert!(parse_args(&args(&["plan", "-o", "a.out", "a.in"])).is_err());
+    assert!(parse_args(&args(&["plan", "--warm-start", "a.out", "a.in"])).is_err());
+    assert!(parse_args(&args(&["plan", "-j", "2", "a.in"])).is_err());
+    assert_eq!("slots/a.in.2.out", slot_output_path("slots/", "inputs/a.in", 2));
+}
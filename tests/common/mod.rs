@@ -0,0 +1,19 @@
+use videos::types::Instance;
+use videos::parser::parse;
+
+// Example instance of the problem statement: 5 videos, 2 endpoints, 4 request
+// descriptions and 3 caches of 100 MB
+pub fn example() -> Instance {
+    let (cache_info, videos, endpoints, requests) = parse("5 2 4 3 100\n\
+    50 50 80 30 110\n\
+    1000 3\n\
+    0 100\n\
+    2 200\n\
+    1 300\n\
+    500 0\n\
+    3 0 1500\n\
+    0 1 1000\n\
+    4 0 500\n\
+    1 0 1000").unwrap();
+    Instance::new(cache_info, videos, endpoints, requests)
+}
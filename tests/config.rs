@@ -0,0 +1,81 @@
+extern crate videos;
+use std::time::Duration;
+use videos::algo::Mode;
+use videos::cli::SolveOptions;
+use videos::context::Limits;
+use videos::config::*;
+
+const CONFIG: &str = "\
+# Applies to every input
+solvers = descent, best-video
+seed = 42
+
+[general]
+time_limit = 10
+
+[descent]
+max_iterations = 1000  # per run
+time_limit = 2.5
+
+[input me_at_the_zoo.in]
+seed = 7
+local_search = false
+
+[input resources/me_at_the_zoo.in local-search]
+max_iterations = 50
+";
+
+#[test]
+fn test_parse_config() {
+    let config = parse_config(CONFIG).unwrap();
+    assert_eq!(Some(vec![Mode::Descent, Mode::BestVideo]), config.general.modes);
+    assert_eq!(Some(42), config.general.seed);
+    assert_eq!(Some(Duration::from_secs(10)), config.general.time_limit);
+    assert_eq!(Some(&Limits {
+        time_limit: Some(Duration::from_millis(2500)),
+        max_iterations: Some(1000)
+    }), config.general.solver_limits.get("descent"));
+    assert_eq!(2, config.inputs.len());
+    assert_eq!(Config::default(), parse_config("\n# nothing\n").unwrap());
+}
+
+#[test]
+fn test_config_errors() {
+    assert_eq!(Err(String::from("line 2: Unknown key tenure")), parse_config("[descent]\ntenure = 3"));
+    assert_eq!(Err(String::from("line 1: Unknown section [annealing]")), parse_config("[annealing]"));
+    assert!(parse_config("[descent").is_err());
+    assert!(parse_config("seed 3").is_err());
+    assert!(parse_config("seed = x").is_err());
+    assert!(parse_config("solvers = descent, nope").is_err());
+    assert!(parse_config("time_limit = -1").is_err());
+    assert!(parse_config("[input a.in]\njobs = 2").is_err());
+    assert!(parse_config("[descent]\nseed = 2").is_err());
+}
+
+#[test]
+fn test_settings_for_input() {
+    let config = parse_config(CONFIG).unwrap();
+    let settings = config.settings_for("resources/me_at_the_zoo.in");
+    assert_eq!(Some(7), settings.seed);
+    assert_eq!(Some(false), settings.local_search);
+    assert_eq!(Some(Duration::from_secs(10)), settings.time_limit);
+    assert_eq!(Some(50), settings.solver_limits.get("local-search").unwrap().max_iterations);
+    assert_eq!(Some(1000), settings.solver_limits.get("descent").unwrap().max_iterations);
+    assert_eq!(config.general, config.settings_for("resources/trending_today.in"));
+}
+
+#[test]
+fn test_command_line_wins() {
+    let settings = parse_config(CONFIG).unwrap().settings_for("me_at_the_zoo.in");
+    let mut options = SolveOptions::new();
+    options.seed = 3;
+    options.command_line.insert("seed");
+    settings.apply(&mut options);
+    assert_eq!(3, options.seed);
+    assert_eq!(vec![Mode::Descent, Mode::BestVideo], options.modes);
+    assert_eq!(Some(Duration::from_secs(10)), options.time_limit);
+    assert!(!options.local_search);
+
+    assert_eq!("[general]\nsolvers = descent, best-video\nseed = 3\ntime_limit = 10\nlocal_search = false\n\
+                [descent]\ntime_limit = 2.5\nmax_iterations = 1000\n", format_effective(&options, &settings));
+}
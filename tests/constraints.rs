@@ -0,0 +1,157 @@
+extern crate videos;
+mod common;
+use std::collections::BTreeSet;
+use videos::types::*;
+use videos::algo::Mode;
+use videos::constraints::{Constraints, ReplicaLimits, parse_constraints};
+use videos::context::Context;
+use videos::placement::Placement;
+use videos::portfolio::{best, reduced_portfolio, unlimited_score};
+use videos::score::{score, validate};
+use common::example;
+
+fn pairs(pairs: &[(i32, i32)]) -> BTreeSet<(CacheId, VideoId)> {
+    pairs.iter().map(|&(cache_id, video_id)| (CacheId(cache_id), VideoId(video_id))).collect()
+}
+
+#[test]
+fn test_parse_constraints() {
+    let constraints = parse_constraints("# Contract\npin 0 1\n\n  forbid 3 0 # Not licensed\nforbid 3 2\n").unwrap();
+    assert_eq!(pairs(&[(1, 0)]), constraints.pinned);
+    assert_eq!(pairs(&[(0, 3), (2, 3)]), constraints.forbidden);
+    assert!(parse_constraints("").unwrap().is_empty());
+
+    assert_eq!(Err(String::from("line 2: Unknown constraint keep")), parse_constraints("pin 0 1\nkeep 0 1"));
+    assert_eq!(Err(String::from("line 1: Invalid video x")), parse_constraints("pin x 1"));
+    assert_eq!(Err(String::from("line 1: Invalid cache -1")), parse_constraints("forbid 0 -1"));
+    assert!(parse_constraints("pin 0").is_err());
+    assert!(parse_constraints("pin 0 1 2").is_err());
+}
+
+#[test]
+fn test_check() {
+    let instance = example();
+    assert_eq!(Ok(()), parse_constraints("pin 0 1\nforbid 3 0").unwrap().check(&instance));
+    assert_eq!(Err(String::from("Invalid cache 3")), parse_constraints("pin 0 3").unwrap().check(&instance));
+    assert_eq!(Err(String::from("Invalid video 5")), parse_constraints("forbid 5 0").unwrap().check(&instance));
+    assert_eq!(Err(String::from("Video 0 is both pinned to and forbidden in cache 1")),
+               parse_constraints("pin 0 1\nforbid 0 1").unwrap().check(&instance));
+    assert_eq!(Err(String::from("Videos pinned to cache 2 take 130 but it can only hold 100")),
+               parse_constraints("pin 2 2\npin 0 2").unwrap().check(&instance));
+}
+
+#[test]
+fn test_check_placement() {
+    let instance = example();
+    let constraints = parse_constraints("pin 0 1\nforbid 3 0").unwrap();
+    let mut placement = Placement::for_instance(&instance);
+    assert_eq!(Err(String::from("Video 0 must be in cache 1")), constraints.check_placement(&placement));
+    assert!(placement.try_add(CacheId(1), VideoId(0)));
+    assert!(placement.try_add(CacheId(0), VideoId(3)));
+    assert_eq!(Err(String::from("Video 3 cannot be in cache 0")), constraints.check_placement(&placement));
+    assert!(placement.remove(CacheId(0), VideoId(3)));
+    assert_eq!(Ok(()), constraints.check_placement(&placement));
+}
+
+#[test]
+fn test_solvers_honor_constraints() {
+    let instance = example();
+    // Video 0 is only requested by an endpoint without caches, so that the
+    // reduction removes it, and video 3 is the most requested one
+    let constraints = parse_constraints("pin 0 1\nforbid 3 0").unwrap();
+    let initial = Placement::with_constraints(&instance, &constraints).unwrap();
+    let runs = reduced_portfolio(&Context::new(), &instance, &initial, &Mode::all(), true, 0).unwrap();
+    for run in &runs {
+        assert!(run.valid, "{} is invalid", run.name());
+        assert_eq!(Ok(()), validate(&instance, &run.placement));
+        assert_eq!(Ok(()), constraints.check_placement(&run.placement), "{}", run.name());
+        assert_eq!(score(&instance, &run.placement), run.score);
+    }
+    assert!(runs.iter().any(|run| run.placement.contains(CacheId(2), VideoId(3))));
+}
+
+#[test]
+fn test_validate_checks_constraints() {
+    let instance = example();
+    let constraints = Constraints {
+        forbidden: pairs(&[(0, 3)]),
+        ..Constraints::default()
+    };
+    let placement = Placement::with_constraints(&instance, &constraints).unwrap();
+    assert_eq!(Ok(()), validate(&instance, &placement));
+    assert!(!placement.fits(CacheId(0), VideoId(3)));
+}
+
+#[test]
+fn test_parse_replicas() {
+    let constraints = parse_constraints("replicas * 0 4\nreplicas 3 1 *\n").unwrap();
+    assert!(constraints.has_replica_limits());
+    assert_eq!(ReplicaLimits { min: 0, max: Some(4) }, constraints.replica_limits(VideoId(0)));
+    assert_eq!(ReplicaLimits { min: 1, max: None }, constraints.replica_limits(VideoId(3)));
+    assert_eq!(ReplicaLimits::default(), parse_constraints("").unwrap().replica_limits(VideoId(0)));
+    assert!(constraints.without_replica_limits().is_empty());
+
+    assert_eq!(Err(String::from("line 1: Maximum 1 below minimum 2")), parse_constraints("replicas 0 2 1"));
+    assert_eq!(Err(String::from("line 1: Invalid minimum -1")), parse_constraints("replicas * -1 1"));
+    assert!(parse_constraints("replicas 0 1").is_err());
+    assert!(parse_constraints("replicas x 0 1").is_err());
+}
+
+#[test]
+fn test_check_replicas() {
+    let instance = example();
+    assert_eq!(Err(String::from("Invalid video 5")), parse_constraints("replicas 5 0 1").unwrap().check(&instance));
+    assert_eq!(Err(String::from("Video 0 is pinned to 2 caches but can be in at most 1")),
+               parse_constraints("pin 0 0\npin 0 1\nreplicas 0 0 1").unwrap().check(&instance));
+    assert_eq!(Err(String::from("Video 1 must be in 3 caches but only 2 can hold it")),
+               parse_constraints("forbid 1 2\nreplicas 1 3 3").unwrap().check(&instance));
+    // Video 4 fits in no cache
+    assert!(parse_constraints("replicas * 1 *").unwrap().check(&instance).is_err());
+}
+
+#[test]
+fn test_check_placement_replicas() {
+    let instance = example();
+    let constraints = parse_constraints("replicas * 0 1\nreplicas 0 1 2").unwrap();
+    let mut placement = Placement::for_instance(&instance);
+    assert_eq!(Err(String::from("Video 0 is in 0 caches but must be in at least 1")),
+               constraints.check_placement(&placement));
+    assert!(placement.try_add(CacheId(0), VideoId(0)));
+    assert!(placement.try_add(CacheId(0), VideoId(3)));
+    assert!(placement.try_add(CacheId(1), VideoId(3)));
+    assert_eq!(Err(String::from("Video 3 is in 2 caches but can be in at most 1")),
+               constraints.check_placement(&placement));
+    assert!(placement.remove(CacheId(1), VideoId(3)));
+    assert_eq!(Ok(()), constraints.check_placement(&placement));
+}
+
+#[test]
+fn test_solvers_honor_replica_limits() {
+    let instance = example();
+    // Video 0 is removed by the reduction but must be in two caches, and video 3
+    // would be worth storing in every cache
+    let constraints = parse_constraints("replicas * 0 1\nreplicas 0 2 2").unwrap();
+    let initial = Placement::with_constraints(&instance, &constraints).unwrap();
+    assert_eq!(2, initial.replicas(VideoId(0)));
+    let runs = reduced_portfolio(&Context::new(), &instance, &initial, &Mode::all(), true, 0).unwrap();
+    for run in &runs {
+        assert!(run.valid, "{} is invalid", run.name());
+        assert_eq!(Ok(()), constraints.check_placement(&run.placement), "{}", run.name());
+        assert_eq!(score(&instance, &run.placement), run.score);
+    }
+
+    let run = best(&runs).unwrap();
+    let unlimited = unlimited_score(&Context::new(), &instance, &initial, run).unwrap();
+    assert!(unlimited > run.score);
+}
+
+#[test]
+fn test_unlimited_score_starts_from_initial() {
+    let instance = example();
+    let constraints = parse_constraints("replicas 0 2 2").unwrap();
+    let mut initial = Placement::with_constraints(&instance, &constraints).unwrap();
+    assert!(initial.try_add(CacheId(1), VideoId(3)));
+    let runs = reduced_portfolio(&Context::new(), &instance, &initial, &[Mode::Dummy], false, 0).unwrap();
+    assert_eq!(score(&instance, &initial), runs[0].score);
+    assert_eq!(Ok(runs[0].score), unlimited_score(&Context::new(), &instance, &initial, &runs[0]));
+}
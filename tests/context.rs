@@ -0,0 +1,59 @@
+extern crate videos;
+use std::thread;
+use std::time::Duration;
+use videos::context::{Context, Limits};
+
+#[test]
+fn test_unbounded() {
+    let context = Context::new();
+    assert!(!context.should_stop(0));
+    assert!(!context.should_stop(u64::MAX));
+}
+
+#[test]
+fn test_max_iterations() {
+    let context = Context::new().with_max_iterations(10);
+    assert!(!context.should_stop(9));
+    assert!(context.should_stop(10));
+}
+
+#[test]
+fn test_time_limit() {
+    let context = Context::new().with_time_limit(Duration::from_millis(10));
+    thread::sleep(Duration::from_millis(20));
+    assert!(context.should_stop(0));
+    assert!(!context.restarted().should_stop(0));
+    assert!(Context::new().with_time_limit(Duration::from_secs(0)).should_stop(0));
+}
+
+#[test]
+fn test_cancel_from_another_thread() {
+    let context = Context::new();
+    let restarted = context.restarted();
+    let shared = context.clone();
+    thread::spawn(move || shared.cancel()).join().unwrap();
+    assert!(context.should_stop(0));
+    assert!(restarted.is_cancelled());
+}
+
+#[test]
+fn test_peak_candidates() {
+    let context = Context::new();
+    context.record_candidates(10);
+    context.record_candidates(5);
+    assert_eq!(10, context.peak_candidates());
+    assert_eq!(0, context.restarted().peak_candidates());
+}
+
+#[test]
+fn test_solver_limits() {
+    let context = Context::new()
+        .with_max_iterations(10)
+        .with_solver_limits("descent", Limits {
+            time_limit: None,
+            max_iterations: Some(3)
+        });
+    assert_eq!(Some(3), context.for_solver("descent").max_iterations());
+    assert_eq!(Some(10), context.for_solver("best-video").max_iterations());
+    assert!(context.for_solver("descent").should_stop(3));
+}
@@ -0,0 +1,49 @@
+extern crate videos;
+mod common;
+use videos::types::*;
+use videos::dense::{Csr, DenseInstance};
+use common::example;
+
+#[test]
+fn test_csr() {
+    let csr = Csr::from_pairs(3, vec![(2, 'a'), (0, 'b'), (2, 'c'), (0, 'd')]);
+    assert_eq!(3, csr.row_count());
+    assert_eq!(&['b', 'd'], csr.row(0));
+    assert!(csr.row(1).is_empty());
+    assert_eq!(&['a', 'c'], csr.row(2));
+    assert_eq!(2, csr.offset(2));
+    assert_eq!(&['b', 'd', 'a', 'c'], csr.items());
+}
+
+#[test]
+fn test_dense_instance() {
+    let dense = DenseInstance::from_instance(&example());
+    assert_eq!(vec![50, 50, 80, 30, 110], dense.video_sizes);
+    assert_eq!(vec![100, 100, 100], dense.cache_capacities);
+    assert_eq!(vec![1000, 500], dense.datacenter_latencies);
+    assert_eq!(&[(CacheId(0), 100), (CacheId(1), 300), (CacheId(2), 200)], dense.endpoint_caches.row(0));
+    assert!(dense.endpoint_caches.row(1).is_empty());
+    assert_eq!(&[(EndpointId(0), 300)], dense.cache_endpoints.row(1));
+    assert_eq!(&[(EndpointId(1), 1000)], dense.video_demand.row(0));
+    assert!(dense.video_demand.row(2).is_empty());
+    assert_eq!(&[(VideoId(1), 1), (VideoId(3), 2), (VideoId(4), 3)], dense.endpoint_demand.row(0));
+    assert_eq!(&[(VideoId(0), 0)], dense.endpoint_demand.row(1));
+}
+
+#[test]
+fn test_dense_instance_sums_duplicate_requests() {
+    let mut instance = example();
+    instance.requests.push(Request::new(VideoId(3), EndpointId(0), 500));
+    let dense = DenseInstance::from_instance(&instance);
+    assert_eq!(&[(EndpointId(0), 2000)], dense.video_demand.row(3));
+    assert_eq!(3, dense.endpoint_demand.row(0).len());
+}
+
+#[test]
+fn test_demand_position() {
+    let dense = DenseInstance::from_instance(&example());
+    assert_eq!(Some(1), dense.demand_position(EndpointId(0), VideoId(3)));
+    assert_eq!(1500, dense.endpoint_requests(1));
+    assert_eq!(Some(3), dense.demand_position(EndpointId(1), VideoId(0)));
+    assert_eq!(None, dense.demand_position(EndpointId(0), VideoId(2)));
+}
@@ -0,0 +1,83 @@
+extern crate videos;
+use videos::types::{CacheId, CacheInfo};
+use videos::parser::parse;
+use videos::generator::{GeneratorParams, generate, format_instance};
+
+#[test]
+fn test_generate_is_reproducible() {
+    let params = GeneratorParams::new(50, 10, 200, 5, 2000);
+    assert_eq!(format_instance(&generate(1, &params)), format_instance(&generate(1, &params)));
+    assert_ne!(format_instance(&generate(1, &params)), format_instance(&generate(2, &params)));
+}
+
+#[test]
+fn test_generate_round_trip() {
+    let params = GeneratorParams::new(50, 10, 200, 5, 2000);
+    let instance = generate(1, &params);
+    assert_eq!(50, instance.videos.len());
+    assert_eq!(10, instance.endpoints.len());
+    assert_eq!(200, instance.requests.len());
+
+    let (cache_info, videos, endpoints, requests) = parse(&format_instance(&instance)).unwrap();
+    assert_eq!(5, cache_info.count);
+    assert_eq!(Some(2000), cache_info.uniform_capacity());
+    for (video, parsed) in instance.videos.iter().zip(videos.iter()) {
+        assert_eq!(video.size, parsed.size);
+    }
+    for (endpoint, parsed) in instance.endpoints.iter().zip(endpoints.iter()) {
+        assert_eq!(endpoint.cache_to_latency, parsed.cache_to_latency);
+    }
+    for (request, parsed) in instance.requests.iter().zip(requests.iter()) {
+        assert_eq!((request.video_id, request.endpoint_id, request.count),
+                   (parsed.video_id, parsed.endpoint_id, parsed.count));
+    }
+}
+
+#[test]
+fn test_per_cache_capacities_round_trip() {
+    let mut instance = generate(1, &GeneratorParams::new(20, 5, 50, 3, 2000));
+    instance.cache_info = CacheInfo::with_capacities(vec![1000, 2000, 3000]);
+    let formatted = format_instance(&instance);
+    assert!(formatted.starts_with("20 5 50 3 *\n1000 2000 3000\n"));
+    let (cache_info, _, _, _) = parse(&formatted).unwrap();
+    assert_eq!(vec![1000, 2000, 3000], cache_info.caches.iter().map(|cache| cache.capacity).collect::<Vec<i32>>());
+}
+
+#[test]
+fn test_tiers_round_trip() {
+    let mut instance = generate(1, &GeneratorParams::new(20, 5, 50, 3, 2000));
+    instance.cache_info.set_parent(CacheId(0), CacheId(2), 150);
+    instance.cache_info.set_parent(CacheId(1), CacheId(2), 250);
+    let formatted = format_instance(&instance);
+    assert!(formatted.ends_with("2\n0 2 150\n1 2 250\n"));
+    let (cache_info, _, _, requests) = parse(&formatted).unwrap();
+    assert_eq!(vec![Some((CacheId(2), 150)), Some((CacheId(2), 250)), None],
+               cache_info.caches.iter().map(|cache| cache.parent).collect::<Vec<Option<(CacheId, i32)>>>());
+    assert_eq!(50, requests.len());
+}
+
+#[test]
+fn test_throughput_limits_round_trip() {
+    let mut instance = generate(1, &GeneratorParams::new(20, 5, 50, 3, 2000));
+    instance.cache_info.set_throughput(CacheId(1), Some(300));
+    let formatted = format_instance(&instance);
+    assert!(formatted.ends_with("0\n* 300 *\n"));
+    let (cache_info, _, _, _) = parse(&formatted).unwrap();
+    assert_eq!(vec![None, Some(300), None],
+               cache_info.caches.iter().map(|cache| cache.throughput).collect::<Vec<Option<i64>>>());
+}
+
+#[test]
+fn test_time_slots_round_trip() {
+    let mut instance = generate(1, &GeneratorParams::new(20, 5, 50, 3, 2000));
+    for (index, request) in instance.requests.iter_mut().enumerate() {
+        request.slot = index as i32 % 4;
+    }
+    let (_, _, _, requests) = parse(&format_instance(&instance)).unwrap();
+    assert_eq!(instance.requests.iter().map(|request| request.slot).collect::<Vec<i32>>(),
+               requests.iter().map(|request| request.slot).collect::<Vec<i32>>());
+
+    // Requests of an instance that is not sliced have no slot
+    let formatted = format_instance(&generate(1, &GeneratorParams::new(20, 5, 50, 3, 2000)));
+    assert_eq!(3, formatted.lines().last().unwrap().split(' ').count());
+}
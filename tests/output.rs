@@ -1,14 +1,15 @@
 extern crate videos;
-use std::collections::BTreeMap;
-use std::collections::BTreeSet;
+use videos::types::*;
+use videos::placement::Placement;
 use videos::output::produce_output;
 
 #[test]
 fn test_produce_output() {
-    let mut result: BTreeMap<i32, BTreeSet<i32>> = BTreeMap::new();
-    result.insert(0, vec![1, 2, 3].iter().cloned().collect());
-    result.insert(1, BTreeSet::new());
-    result.insert(2, vec![1, 4].iter().cloned().collect());
+    let videos: Vec<Video> = (0..5).map(|id| Video::new(VideoId(id), 10)).collect();
+    let mut result = Placement::new(&CacheInfo::new(3, 100), &videos);
+    for &(cache_id, video_id) in &[(0, 1), (0, 2), (0, 3), (2, 4), (2, 1)] {
+        result.try_add(CacheId(cache_id), VideoId(video_id));
+    }
 
-    assert_eq!("2\n0 1 2 3\n2 1 4\n", produce_output(result));
-}
\ No newline at end of file
+    assert_eq!("2\n0 1 2 3\n2 1 4\n", produce_output(&result));
+}
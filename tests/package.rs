@@ -0,0 +1,29 @@
+extern crate videos;
+use std::fs;
+use videos::package::*;
+
+#[test]
+fn test_submission_name() {
+    assert_eq!("me_at_the_zoo.out", submission_name("resources/me_at_the_zoo.in"));
+    assert_eq!("kittens.txt.out", submission_name("kittens.txt"));
+}
+
+#[test]
+fn test_source_files() {
+    let files = source_files(".").unwrap();
+    assert_eq!("Cargo.toml", files[0]);
+    assert!(files.contains(&String::from("src/lib.rs")));
+    assert!(files.contains(&String::from("tests/package.rs")));
+    assert!(!files.iter().any(|file| file.starts_with("target") || file.starts_with("resources")));
+    let mut sorted = files.clone();
+    sorted.sort();
+    assert_eq!(sorted, files);
+    assert!(source_files("does_not_exist").is_err());
+}
+
+#[test]
+fn test_source_zip() {
+    let zip = source_zip(".").unwrap();
+    let lib = fs::read("src/lib.rs").unwrap();
+    assert!(zip.windows(lib.len()).any(|window| window == lib.as_slice()));
+}
@@ -1,6 +1,7 @@
 extern crate videos;
 
-use videos::parser::parse;
+use videos::types::*;
+use videos::parser::{parse, parse_output};
 
 #[test]
 fn test_invalid_header() {
@@ -20,6 +21,19 @@ fn test_invalid_video_count() {
     assert!(result_option.is_none());
 }
 
+#[test]
+fn test_invalid_values() {
+    // Out of i32 range
+    assert!(parse("2 0 0 1 99999999999\n12 34").is_none());
+    // Negative size
+    assert!(parse("2 0 0 1 100\n12 -34").is_none());
+    // Unknown video, endpoint and cache
+    assert!(parse("1 1 1 1 100\n12\n1000 0\n1 0 10").is_none());
+    assert!(parse("1 1 1 1 100\n12\n1000 0\n0 1 10").is_none());
+    assert!(parse("1 1 0 1 100\n12\n1000 1\n1 100").is_none());
+    assert!(parse("1 1 1 1 100\n12\n1000 1\n0 100\n0 0 10").is_some());
+}
+
 #[test]
 fn test_cache_info_and_videos() {
     let result_option = parse("2 0 0 123 456\n12 34");
@@ -27,11 +41,11 @@ fn test_cache_info_and_videos() {
 
     let (cache_info, videos, endpoints, _) = result_option.unwrap();
     assert_eq!(123, cache_info.count);
-    assert_eq!(456, cache_info.capacity);
+    assert_eq!(Some(456), cache_info.uniform_capacity());
     assert_eq!(2, videos.len());
-    assert_eq!(0, videos[0].id);
+    assert_eq!(VideoId(0), videos[0].id);
     assert_eq!(12, videos[0].size);
-    assert_eq!(1, videos[1].id);
+    assert_eq!(VideoId(1), videos[1].id);
     assert_eq!(34, videos[1].size);
     assert_eq!(0, endpoints.len());
 }
@@ -53,7 +67,7 @@ fn test_end_to_end() {
 
     let (cache_info, videos, endpoints, requests) = result_option.unwrap();
     assert_eq!(3, cache_info.count);
-    assert_eq!(100, cache_info.capacity);
+    assert_eq!(Some(100), cache_info.uniform_capacity());
     assert_eq!(5, videos.len());
     assert_eq!(50, videos[0].size);
     assert_eq!(50, videos[1].size);
@@ -61,23 +75,100 @@ fn test_end_to_end() {
     assert_eq!(30, videos[3].size);
     assert_eq!(110, videos[4].size);
     assert_eq!(2, endpoints.len());
-    assert_eq!(4, endpoints[0].cache_to_latency.len());
-    assert_eq!(1000, *endpoints[0].cache_to_latency.get(&-1).unwrap());
-    assert_eq!(100, *endpoints[0].cache_to_latency.get(&0).unwrap());
-    assert_eq!(300, *endpoints[0].cache_to_latency.get(&1).unwrap());
-    assert_eq!(100, *endpoints[0].cache_to_latency.get(&2).unwrap());
-    assert_eq!(500, *endpoints[1].cache_to_latency.get(&-1).unwrap());
+    assert_eq!(3, endpoints[0].cache_to_latency.len());
+    assert_eq!(1000, endpoints[0].datacenter_latency);
+    assert_eq!(100, *endpoints[0].cache_to_latency.get(&CacheId(0)).unwrap());
+    assert_eq!(300, *endpoints[0].cache_to_latency.get(&CacheId(1)).unwrap());
+    assert_eq!(100, *endpoints[0].cache_to_latency.get(&CacheId(2)).unwrap());
+    assert_eq!(500, endpoints[1].datacenter_latency);
+    assert!(endpoints[1].cache_to_latency.is_empty());
     assert_eq!(4, requests.len());
-    assert_eq!(3, requests[0].video_id);
-    assert_eq!(0, requests[0].endpoint_id);
+    assert_eq!(VideoId(3), requests[0].video_id);
+    assert_eq!(EndpointId(0), requests[0].endpoint_id);
     assert_eq!(1500, requests[0].count);
-    assert_eq!(0, requests[1].video_id);
-    assert_eq!(1, requests[1].endpoint_id);
+    assert_eq!(VideoId(0), requests[1].video_id);
+    assert_eq!(EndpointId(1), requests[1].endpoint_id);
     assert_eq!(1000, requests[1].count);
-    assert_eq!(4, requests[2].video_id);
-    assert_eq!(0, requests[2].endpoint_id);
+    assert_eq!(VideoId(4), requests[2].video_id);
+    assert_eq!(EndpointId(0), requests[2].endpoint_id);
     assert_eq!(500, requests[2].count);
-    assert_eq!(1, requests[3].video_id);
-    assert_eq!(0, requests[3].endpoint_id);
+    assert_eq!(VideoId(1), requests[3].video_id);
+    assert_eq!(EndpointId(0), requests[3].endpoint_id);
     assert_eq!(1000, requests[3].count);
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_output() {
+    let placement = parse_output("3\n0 2\n1 3 1\n2 0 1\n").unwrap();
+    assert_eq!(3, placement.len());
+    assert_eq!(vec![VideoId(2)], placement.get(&CacheId(0)).unwrap().iter().cloned().collect::<Vec<VideoId>>());
+    assert_eq!(vec![VideoId(1), VideoId(3)], placement.get(&CacheId(1)).unwrap().iter().cloned().collect::<Vec<VideoId>>());
+    assert_eq!(vec![VideoId(0), VideoId(1)], placement.get(&CacheId(2)).unwrap().iter().cloned().collect::<Vec<VideoId>>());
+}
+
+#[test]
+fn test_parse_invalid_output() {
+    assert!(parse_output("").is_none());
+    assert!(parse_output("2\n0 1\n").is_none());
+    assert!(parse_output("2\n0 1\n0 2\n").is_none());
+    assert!(parse_output("1\n0 a\n").is_none());
+}
+
+#[test]
+fn test_per_cache_capacities() {
+    let (cache_info, videos, _, _) = parse("2 0 0 3 *\n10 20 30\n12 34").unwrap();
+    assert_eq!(3, cache_info.count);
+    assert_eq!(None, cache_info.uniform_capacity());
+    assert_eq!(vec![10, 20, 30], cache_info.caches.iter().map(|cache| cache.capacity).collect::<Vec<i32>>());
+    assert_eq!(60, cache_info.total_capacity());
+    assert_eq!(2, videos.len());
+
+    assert!(parse("2 0 0 3 *\n10 20\n12 34").is_none());
+    assert!(parse("2 0 0 3 x\n12 34").is_none());
+}
+
+#[test]
+fn test_cache_tiers() {
+    let input = "1 1 1 3 100\n12\n1000 1\n0 100\n0 0 10\n";
+    let (cache_info, _, _, requests) = parse(&format!("{}2\n0 2 200\n1 2 50", input)).unwrap();
+    assert_eq!(Some((CacheId(2), 200)), cache_info.caches[0].parent);
+    assert_eq!(Some((CacheId(2), 50)), cache_info.caches[1].parent);
+    assert_eq!(None, cache_info.caches[2].parent);
+    assert_eq!(1, requests.len());
+    assert!(!parse(input).unwrap().0.has_tiers());
+
+    // Count mismatch, unknown or duplicate caches, own parent and cycles
+    assert!(parse(&format!("{}2\n0 2 200", input)).is_none());
+    assert!(parse(&format!("{}1\n0 3 200", input)).is_none());
+    assert!(parse(&format!("{}2\n0 2 200\n0 1 200", input)).is_none());
+    assert!(parse(&format!("{}1\n1 1 200", input)).is_none());
+    assert!(parse(&format!("{}2\n0 1 200\n1 0 200", input)).is_none());
+    assert!(parse(&format!("{}0 0 10", input)).is_none());
+}
+
+#[test]
+fn test_throughput_limits() {
+    let input = "1 1 1 3 100\n12\n1000 1\n0 100\n0 0 10\n";
+    let (cache_info, _, _, _) = parse(&format!("{}0\n500 * 20", input)).unwrap();
+    assert_eq!(vec![Some(500), None, Some(20)],
+               cache_info.caches.iter().map(|cache| cache.throughput).collect::<Vec<Option<i64>>>());
+    assert!(cache_info.has_throughput_limits());
+    let (cache_info, _, _, _) = parse(&format!("{}1\n0 2 200\n* * 20", input)).unwrap();
+    assert_eq!(Some((CacheId(2), 200)), cache_info.caches[0].parent);
+    assert_eq!(Some(20), cache_info.caches[2].throughput);
+    assert!(!parse(input).unwrap().0.has_throughput_limits());
+
+    assert!(parse(&format!("{}0\n500 20", input)).is_none());
+    assert!(parse(&format!("{}0\n500 -1 20", input)).is_none());
+    assert!(parse(&format!("{}0\n500 * 20\n1", input)).is_none());
+}
+
+#[test]
+fn test_time_slots() {
+    let (_, _, _, requests) = parse("1 1 3 1 100\n12\n1000 1\n0 100\n0 0 10 2\n0 0 20\n0 0 30 0").unwrap();
+    assert_eq!(vec![2, 0, 0], requests.iter().map(|request| request.slot).collect::<Vec<i32>>());
+    assert!(parse("1 1 1 1 100\n12\n1000 1\n0 100\n0 0 10 -1").is_none());
+    assert!(parse("1 1 1 1 100\n12\n1000 1\n0 100\n0 0 10 1 1").is_none());
+    assert!(parse(&format!("1 1 1 1 100\n12\n1000 1\n0 100\n0 0 10 {}", MAX_SLOTS - 1)).is_some());
+    assert!(parse(&format!("1 1 1 1 100\n12\n1000 1\n0 100\n0 0 10 {}", MAX_SLOTS)).is_none());
+    assert!(parse("1 1 1 1 100\n12\n1000 1\n0 100\n0 0 10 2000000000").is_none());
+}
@@ -0,0 +1,139 @@
+extern crate videos;
+mod common;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use videos::types::*;
+use videos::placement::Placement;
+use videos::constraints::parse_constraints;
+use common::example;
+
+#[test]
+fn test_try_add_and_remove() {
+    let mut placement = Placement::for_instance(&example());
+    assert_eq!(3, placement.cache_count());
+    assert!(placement.fits(CacheId(1), VideoId(3)));
+    assert!(placement.try_add(CacheId(1), VideoId(3)));
+    assert!(!placement.fits(CacheId(1), VideoId(3)));
+    assert!(!placement.try_add(CacheId(1), VideoId(3)));
+    assert!(placement.try_add(CacheId(1), VideoId(1)));
+    assert!(placement.contains(CacheId(1), VideoId(1)));
+    assert_eq!(80, placement.used(CacheId(1)));
+    assert_eq!(20, placement.free_space(CacheId(1)));
+
+    assert!(!placement.fits(CacheId(1), VideoId(0)));
+    assert!(!placement.try_add(CacheId(1), VideoId(0)));
+    assert!(!placement.try_add(CacheId(0), VideoId(4)));
+    assert_eq!(0, placement.used(CacheId(0)));
+
+    assert!(placement.remove(CacheId(1), VideoId(3)));
+    assert!(!placement.remove(CacheId(1), VideoId(3)));
+    assert!(!placement.contains(CacheId(1), VideoId(3)));
+    assert_eq!(50, placement.used(CacheId(1)));
+    assert!(placement.try_add(CacheId(1), VideoId(0)));
+    assert_eq!(0, placement.free_space(CacheId(1)));
+
+    let caches: Vec<(CacheId, usize)> = placement.iter().map(|(cache_id, video_ids)| (cache_id, video_ids.len())).collect();
+    assert_eq!(vec![(CacheId(0), 0), (CacheId(1), 2), (CacheId(2), 0)], caches);
+}
+
+#[test]
+fn test_from_map() {
+    let mut instance = example();
+    instance.cache_info = CacheInfo::with_capacities(vec![100, 50, 130]);
+
+    let mut map: BTreeMap<CacheId, BTreeSet<VideoId>> = BTreeMap::new();
+    map.insert(CacheId(0), [0, 1].iter().cloned().map(VideoId).collect());
+    map.insert(CacheId(2), [4].iter().cloned().map(VideoId).collect());
+    let placement = Placement::from_map(&instance, &map).unwrap();
+    assert_eq!(100, placement.used(CacheId(0)));
+    assert_eq!(20, placement.free_space(CacheId(2)));
+    assert!(placement.videos(CacheId(1)).is_empty());
+    assert_eq!(map, placement.to_map().into_iter().filter(|(_, video_ids)| !video_ids.is_empty()).collect());
+
+    map.insert(CacheId(1), [2].iter().cloned().map(VideoId).collect());
+    assert_eq!(Err(String::from("Cache 1 stores 80 but can only hold 50")), Placement::from_map(&instance, &map));
+    map.insert(CacheId(1), [5].iter().cloned().map(VideoId).collect());
+    assert_eq!(Err(String::from("Invalid video 5 in cache 1")), Placement::from_map(&instance, &map));
+    map.remove(&CacheId(1));
+    map.insert(CacheId(3), BTreeSet::new());
+    assert_eq!(Err(String::from("Invalid cache 3")), Placement::from_map(&instance, &map));
+}
+
+#[test]
+fn test_submission_round_trip() {
+    let instance = example();
+    let placement = Placement::from_submission(&instance, "3\n0 2\n1 3 1\n2 0 1\n").unwrap();
+    assert_eq!(vec![VideoId(1), VideoId(3)], placement.videos(CacheId(1)).iter().cloned().collect::<Vec<VideoId>>());
+    assert_eq!("3\n0 2\n1 1 3\n2 0 1\n", placement.to_submission());
+    assert_eq!(Ok(placement.clone()), Placement::from_submission(&instance, &placement.to_submission()));
+
+    assert_eq!(Err(String::from("Unable to parse submission")), Placement::from_submission(&instance, "x"));
+    assert_eq!(Err(String::from("Invalid submission: Cache 0 stores 160 but can only hold 100")),
+               Placement::from_submission(&instance, "1\n0 0 4\n"));
+}
+
+#[test]
+fn test_is_for() {
+    let instance = example();
+    let placement = Placement::for_instance(&instance);
+    assert!(placement.is_for(&instance));
+
+    let mut other = example();
+    other.cache_info = CacheInfo::with_capacities(vec![100, 100, 50]);
+    assert!(!placement.is_for(&other));
+}
+
+#[test]
+fn test_constraints() {
+    let instance = example();
+    let constraints = parse_constraints("pin 0 1\npin 3 1\nforbid 1 2").unwrap();
+    let mut placement = Placement::with_constraints(&instance, &constraints).unwrap();
+    assert!(placement.contains(CacheId(1), VideoId(0)));
+    assert_eq!(80, placement.used(CacheId(1)));
+    assert!(!placement.remove(CacheId(1), VideoId(0)));
+    assert!(!placement.fits(CacheId(2), VideoId(1)));
+    assert!(!placement.try_add(CacheId(2), VideoId(1)));
+    assert!(placement.try_add(CacheId(0), VideoId(1)));
+
+    let mut other = Placement::for_instance(&instance);
+    assert!(other.try_add(CacheId(2), VideoId(1)));
+    assert!(other.try_add(CacheId(2), VideoId(3)));
+    placement.extend(&other);
+    assert!(!placement.contains(CacheId(2), VideoId(1)));
+    assert!(placement.contains(CacheId(2), VideoId(3)));
+
+    let pinned = placement.pinned();
+    assert_eq!(vec![(CacheId(1), VideoId(0)), (CacheId(1), VideoId(3))],
+               pinned.iter().flat_map(|(cache_id, video_ids)| video_ids.iter().map(move |&video_id| (cache_id, video_id)))
+                   .collect::<Vec<(CacheId, VideoId)>>());
+    assert_eq!(&constraints, pinned.constraints());
+    assert!(Placement::with_constraints(&instance, &parse_constraints("pin 4 0\npin 0 0").unwrap()).is_err());
+}
+
+#[test]
+fn test_replica_limits() {
+    let instance = example();
+    let constraints = parse_constraints("replicas * 0 2\nreplicas 3 1 2").unwrap();
+    let mut placement = Placement::with_constraints(&instance, &constraints).unwrap();
+    // Video 3 goes where it saves the most
+    assert_eq!(1, placement.replicas(VideoId(3)));
+    assert!(placement.contains(CacheId(0), VideoId(3)));
+    assert!(!placement.remove(CacheId(0), VideoId(3)));
+    assert!(!placement.can_remove(CacheId(0), VideoId(3)));
+
+    assert!(placement.try_add(CacheId(1), VideoId(3)));
+    assert!(!placement.fits(CacheId(2), VideoId(3)));
+    assert!(!placement.try_add(CacheId(2), VideoId(3)));
+    assert!(placement.remove(CacheId(0), VideoId(3)));
+    assert_eq!(vec![(CacheId(1), VideoId(3))],
+               placement.pinned().iter().flat_map(|(cache_id, video_ids)| {
+                   video_ids.iter().map(move |&video_id| (cache_id, video_id))
+               }).collect::<Vec<(CacheId, VideoId)>>());
+
+    let mut map: BTreeMap<CacheId, BTreeSet<VideoId>> = BTreeMap::new();
+    map.insert(CacheId(0), vec![VideoId(0), VideoId(1)].into_iter().collect());
+    map.insert(CacheId(1), vec![VideoId(1)].into_iter().collect());
+    let placement = Placement::from_map(&instance, &map).unwrap();
+    assert_eq!(2, placement.replicas(VideoId(1)));
+    assert_eq!(0, placement.replicas(VideoId(2)));
+}
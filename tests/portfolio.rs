@@ -0,0 +1,61 @@
+extern crate videos;
+mod common;
+use videos::types::{CacheId, CacheInfo};
+use videos::placement::Placement;
+use videos::context::Context;
+use videos::algo::Mode;
+use videos::score::score;
+use videos::portfolio::{portfolio, best};
+use videos::search::local_search;
+use videos::generator::{GeneratorParams, generate};
+use common::example;
+
+#[test]
+fn test_portfolio_runs_every_mode() {
+    let instance = example();
+    let runs = portfolio(&Context::new(), &instance, &Placement::for_instance(&instance), &Mode::all(), true, 0);
+    assert_eq!(2 * Mode::all().len(), runs.len());
+    assert!(runs.iter().all(|run| run.valid));
+    for run in &runs {
+        assert_eq!(run.score, score(&instance, &run.placement));
+    }
+}
+
+#[test]
+fn test_portfolio_keeps_best() {
+    let instance = example();
+    let runs = portfolio(&Context::new(), &instance, &Placement::for_instance(&instance),
+                         &[Mode::Dummy, Mode::DescentAmend], false, 0);
+    assert_eq!(2, runs.len());
+    let run = best(&runs).unwrap();
+    assert_eq!(Mode::DescentAmend, run.mode);
+    assert!(runs.iter().all(|other| other.score <= run.score));
+}
+
+#[test]
+fn test_portfolio_warm_start() {
+    let instance = generate(3, &GeneratorParams::new(200, 20, 1000, 10, 3000));
+    let initial = local_search(&Context::new().with_max_iterations(3), &instance, Placement::for_instance(&instance), 0);
+    let initial_score = score(&instance, &initial);
+    assert!(initial_score > 0);
+
+    let runs = portfolio(&Context::new(), &instance, &initial, &Mode::all(), true, 0);
+    for run in &runs {
+        assert!(run.valid);
+        assert!(run.score >= initial_score, "{} is worse than the initial placement", run.name());
+        for (cache_id, video_ids) in initial.iter() {
+            assert!(video_ids.is_subset(run.placement.videos(cache_id)) || run.local_search);
+        }
+    }
+}
+
+#[test]
+fn test_portfolio_per_cache_capacities() {
+    let mut instance = generate(5, &GeneratorParams::new(100, 10, 500, 6, 2000));
+    instance.cache_info = CacheInfo::with_capacities(vec![0, 500, 1000, 2000, 4000, 8000]);
+    let runs = portfolio(&Context::new(), &instance, &Placement::for_instance(&instance), &Mode::all(), true, 0);
+    assert!(runs.iter().all(|run| run.valid));
+    assert!(runs.iter().all(|run| run.placement.videos(CacheId(0)).is_empty()));
+    let best_run = best(&runs).unwrap();
+    assert!(best_run.placement.used(CacheId(5)) > 4000);
+}
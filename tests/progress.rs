@@ -0,0 +1,109 @@
+extern crate videos;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use videos::context::Context;
+use videos::progress::*;
+
+struct RecordingReporter {
+    verbosity: Verbosity,
+    lines: Mutex<Vec<String>>
+}
+
+impl RecordingReporter {
+    fn new(verbosity: Verbosity) -> RecordingReporter {
+        RecordingReporter {
+            verbosity,
+            lines: Mutex::new(Vec::new())
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().clone()
+    }
+}
+
+impl Reporter for RecordingReporter {
+    fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    fn message(&self, message: &str) {
+        self.lines.lock().unwrap().push(String::from(message));
+    }
+
+    fn progress(&self, progress: &Progress) {
+        self.lines.lock().unwrap().push(format_progress(progress));
+    }
+}
+
+#[test]
+fn test_format_progress() {
+    let progress = Progress {
+        label: "descent",
+        done: 25,
+        total: Some(100),
+        best_score: Some(1234),
+        elapsed: Duration::from_secs(10)
+    };
+    assert_eq!(Some(25.), progress.percent());
+    assert_eq!(Some(Duration::from_secs(30)), progress.eta());
+    assert_eq!("descent: 25.0%, best 1234, ETA 30s", format_progress(&progress));
+
+    let progress = Progress {
+        label: "search",
+        done: 3,
+        total: None,
+        best_score: None,
+        elapsed: Duration::from_secs(10)
+    };
+    assert_eq!("search: 3", format_progress(&progress));
+}
+
+#[test]
+fn test_tracker_is_throttled() {
+    let reporter = RecordingReporter::new(Verbosity::Normal);
+    {
+        let mut tracker = Tracker::new(&reporter, "task", Some(1000)).with_interval(Duration::from_secs(3600));
+        for done in 0..1000 {
+            tracker.update(done, None);
+        }
+        tracker.finish(1000, Some(5));
+    }
+    assert_eq!(vec![String::from("task: 0.0%"), String::from("task: 100.0%, best 5, ETA 0s")], reporter.lines());
+}
+
+#[test]
+fn test_silent_tracker() {
+    let reporter = RecordingReporter::new(Verbosity::Silent);
+    {
+        let mut tracker = Tracker::new(&reporter, "task", None);
+        tracker.update(1, None);
+        tracker.finish(2, None);
+    }
+    assert!(reporter.lines().is_empty());
+}
+
+#[test]
+fn test_context_log_verbosity() {
+    let reporter = Arc::new(RecordingReporter::new(Verbosity::Normal));
+    let context = Context::new().with_reporter(reporter.clone());
+    context.log(Verbosity::Normal, || String::from("normal"));
+    context.log(Verbosity::Verbose, || String::from("verbose"));
+    assert_eq!(vec![String::from("normal")], reporter.lines());
+}
+
+#[test]
+fn test_prefixed_reporter() {
+    let reporter = Arc::new(RecordingReporter::new(Verbosity::Normal));
+    let prefixed = PrefixedReporter::new("a.in", reporter.clone());
+    prefixed.message("started");
+    prefixed.progress(&Progress {
+        label: "search",
+        done: 3,
+        total: None,
+        best_score: None,
+        elapsed: Duration::from_secs(1)
+    });
+    assert_eq!(Verbosity::Normal, prefixed.verbosity());
+    assert_eq!(vec![String::from("a.in: started"), String::from("a.in: search: 3")], reporter.lines());
+}
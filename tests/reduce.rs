@@ -0,0 +1,167 @@
+extern crate videos;
+use std::collections::BTreeMap;
+use videos::types::*;
+use videos::placement::Placement;
+use videos::constraints::parse_constraints;
+use videos::context::Context;
+use videos::algo::Mode;
+use videos::score::{score, validate};
+use videos::search::local_search;
+use videos::reduce::{reduce, ReductionStats};
+use videos::portfolio::reduced_portfolio;
+use videos::generator::{GeneratorParams, generate};
+
+// Video 1 is too large, video 3 is never requested, cache 1 is slower than
+// the datacenter for endpoint 0, endpoint 1 has no cache and cache 2 is not
+// connected to any endpoint
+fn instance() -> Instance {
+    let videos = vec![Video::new(VideoId(0), 50), Video::new(VideoId(1), 150), Video::new(VideoId(2), 30), Video::new(VideoId(3), 20),
+                      Video::new(VideoId(4), 40)];
+    let mut endpoint0_relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+    endpoint0_relation.insert(CacheId(0), 100);
+    endpoint0_relation.insert(CacheId(1), 1000);
+    endpoint0_relation.insert(CacheId(3), 200);
+    let endpoint1_relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+    let endpoints = vec![Endpoint::new(EndpointId(0), 1000, endpoint0_relation), Endpoint::new(EndpointId(1), 500, endpoint1_relation)];
+    let requests = vec![Request::new(VideoId(0), EndpointId(0), 100), Request::new(VideoId(1), EndpointId(0), 200), Request::new(VideoId(2), EndpointId(0), 300),
+                        Request::new(VideoId(4), EndpointId(1), 400), Request::new(VideoId(4), EndpointId(0), 500)];
+    Instance::new(CacheInfo::new(4, 100), videos, endpoints, requests)
+}
+
+#[test]
+fn test_reduce() {
+    let original = instance();
+    let reduction = reduce(&original);
+    assert_eq!(ReductionStats {
+        oversized_videos: 1,
+        unrequested_videos: 1,
+        slow_links: 1,
+        disconnected_endpoints: 1,
+        unused_caches: 2,
+        removed_requests: 2
+    }, reduction.stats);
+
+    let reduced = &reduction.instance;
+    assert_eq!(2, reduced.cache_info.count);
+    assert_eq!(vec![50, 30, 40], reduced.videos.iter().map(|video| video.size).collect::<Vec<i32>>());
+    assert_eq!(1, reduced.endpoints.len());
+    assert_eq!(1000, reduced.endpoints[0].datacenter_latency);
+    assert_eq!(vec![(CacheId(0), 100), (CacheId(1), 200)],
+               reduced.endpoints[0].cache_to_latency.iter().map(|(&k, &v)| (k, v)).collect::<Vec<(CacheId, i32)>>());
+    assert_eq!(3, reduced.requests.len());
+    assert_eq!((VideoId(2), EndpointId(0), 500), (reduced.requests[2].video_id, reduced.requests[2].endpoint_id, reduced.requests[2].count));
+}
+
+#[test]
+fn test_reduce_per_cache_capacities() {
+    let mut original = instance();
+    original.cache_info = CacheInfo::with_capacities(vec![100, 100, 100, 150]);
+    let reduction = reduce(&original);
+    assert_eq!(0, reduction.stats.oversized_videos);
+    assert_eq!(vec![100, 150], reduction.instance.cache_info.caches.iter()
+        .map(|cache| cache.capacity)
+        .collect::<Vec<i32>>());
+}
+
+#[test]
+fn test_reduce_keeps_throughput_limits() {
+    let mut original = instance();
+    original.cache_info.set_throughput(CacheId(3), Some(700));
+    let reduction = reduce(&original);
+    assert_eq!(vec![None, Some(700)], reduction.instance.cache_info.caches.iter()
+        .map(|cache| cache.throughput)
+        .collect::<Vec<Option<i64>>>());
+}
+
+#[test]
+fn test_reduce_rejects_pinned_videos_that_do_not_fit() {
+    let original = instance();
+    // Same caches and videos, twice as large caches
+    let mut larger = instance();
+    larger.cache_info = CacheInfo::new(4, 200);
+
+    // Videos 1 and 3 are removed by the reduction but take 170 in cache 0
+    let pinned = Placement::with_constraints(&larger, &parse_constraints("pin 1 0\npin 3 0").unwrap()).unwrap();
+    assert_eq!(Err(String::from("Videos kept in cache 0 take 170 but it can only hold 100")),
+               reduce(&original).with_constraints_of(&pinned).map(|_| ()));
+
+    let pinned = Placement::with_constraints(&larger, &parse_constraints("pin 0 0\npin 2 0\npin 4 0").unwrap()).unwrap();
+    let reduction = reduce(&original).with_constraints_of(&Placement::for_instance(&original)).unwrap();
+    assert!(reduction.to_reduced(&pinned).is_err());
+    assert!(reduced_portfolio(&Context::new(), &original, &pinned, &[Mode::Descent], false, 0).is_err());
+}
+
+#[test]
+fn test_reduce_keeps_time_slots() {
+    let mut original = instance();
+    original.requests[2].slot = 1;
+    let reduction = reduce(&original);
+    assert_eq!(2, reduction.instance.slot_count());
+    let requests = reduction.instance.for_slot(1).requests;
+    assert_eq!(1, requests.len());
+    assert_eq!((VideoId(1), 300), (requests[0].video_id, requests[0].count));
+}
+
+#[test]
+fn test_reduce_with_constraints() {
+    let original = instance();
+    // Video 3 is removed but pinned to cache 0, and video 2 may only be in the
+    // cache 2 it is pinned to, which is removed too
+    let constraints = parse_constraints("pin 3 0\npin 2 2\nreplicas 2 0 1").unwrap();
+    let initial = Placement::with_constraints(&original, &constraints).unwrap();
+    let reduction = reduce(&original).with_constraints_of(&initial).unwrap();
+    assert_eq!(vec![80, 100], reduction.instance.cache_info.caches.iter()
+        .map(|cache| cache.capacity)
+        .collect::<Vec<i32>>());
+
+    let mut reduced = reduction.to_reduced(&initial).unwrap();
+    assert!(!reduced.fits(CacheId(1), VideoId(1)));
+    assert!(reduced.try_add(CacheId(0), VideoId(0)));
+    let restored = reduction.to_original(&reduced);
+    assert_eq!(Ok(()), validate(&original, &restored));
+    assert!(restored.contains(CacheId(0), VideoId(3)));
+    assert!(restored.contains(CacheId(2), VideoId(2)));
+    assert!(restored.contains(CacheId(0), VideoId(0)));
+}
+
+#[test]
+fn test_reduce_maps_placements() {
+    let original = instance();
+    let reduction = reduce(&original);
+
+    let mut placement = Placement::for_instance(&reduction.instance);
+    for &(cache_id, video_id) in &[(0, 0), (0, 2), (1, 1)] {
+        assert!(placement.try_add(CacheId(cache_id), VideoId(video_id)));
+    }
+    let restored = reduction.to_original(&placement);
+    assert!(validate(&original, &restored).is_ok());
+    assert_eq!(vec![VideoId(0), VideoId(4)], restored.videos(CacheId(0)).iter().cloned().collect::<Vec<VideoId>>());
+    assert_eq!(vec![VideoId(2)], restored.videos(CacheId(3)).iter().cloned().collect::<Vec<VideoId>>());
+    assert_eq!(placement, reduction.to_reduced(&restored).unwrap());
+
+    let mut with_removed = restored.clone();
+    assert!(with_removed.try_add(CacheId(3), VideoId(3)));
+    assert!(with_removed.try_add(CacheId(2), VideoId(0)));
+    assert_eq!(placement, reduction.to_reduced(&with_removed).unwrap());
+}
+
+#[test]
+fn test_solve_reduced() {
+    let mut params = GeneratorParams::new(200, 20, 1000, 10, 600);
+    params.connectivity = 0.2;
+    let original = generate(9, &params);
+    let reduction = reduce(&original);
+    assert!(reduction.instance.videos.len() < original.videos.len());
+
+    let reduced = local_search(&Context::new(), &reduction.instance, Placement::for_instance(&reduction.instance), 0);
+    let restored = reduction.to_original(&reduced);
+    assert!(validate(&original, &restored).is_ok());
+    assert!(score(&original, &restored) > 0);
+
+    let runs = reduced_portfolio(&Context::new(), &original, &Placement::for_instance(&original), &[Mode::Descent], true,
+                                 0).unwrap();
+    for run in &runs {
+        assert!(run.valid);
+        assert_eq!(score(&original, &run.placement), run.score);
+    }
+}
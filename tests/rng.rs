@@ -0,0 +1,61 @@
+extern crate videos;
+use videos::rng::Rng;
+
+#[test]
+fn test_same_seed_same_sequence() {
+    let mut rng1 = Rng::new(42);
+    let mut rng2 = Rng::new(42);
+    for _ in 0..100 {
+        assert_eq!(rng1.next_u64(), rng2.next_u64());
+    }
+}
+
+#[test]
+fn test_different_seed_different_sequence() {
+    let mut rng1 = Rng::new(1);
+    let mut rng2 = Rng::new(2);
+    let values1: Vec<u64> = (0..10).map(|_| rng1.next_u64()).collect();
+    let values2: Vec<u64> = (0..10).map(|_| rng2.next_u64()).collect();
+    assert_ne!(values1, values2);
+}
+
+#[test]
+fn test_range() {
+    let mut rng = Rng::new(0);
+    let mut seen = [false; 5];
+    for _ in 0..1000 {
+        let value = rng.range(-2, 3);
+        assert!((-2..3).contains(&value));
+        seen[(value + 2) as usize] = true;
+    }
+    assert!(seen.iter().all(|&seen| seen));
+
+    for _ in 0..1000 {
+        let value = rng.next_f64();
+        assert!((0.0..1.0).contains(&value));
+    }
+}
+
+#[test]
+fn test_shuffle() {
+    let mut rng = Rng::new(7);
+    let mut values: Vec<i32> = (0..50).collect();
+    rng.shuffle(&mut values);
+    assert_ne!((0..50).collect::<Vec<i32>>(), values);
+    values.sort();
+    assert_eq!((0..50).collect::<Vec<i32>>(), values);
+}
+
+#[test]
+fn test_weighted_choice() {
+    let mut rng = Rng::new(3);
+    assert_eq!(None, rng.weighted_choice(&[]));
+    assert_eq!(None, rng.weighted_choice(&[0, 0]));
+
+    let mut counts = [0; 3];
+    for _ in 0..1000 {
+        counts[rng.weighted_choice(&[1, 0, 9]).unwrap()] += 1;
+    }
+    assert_eq!(0, counts[1]);
+    assert!(counts[2] > 5 * counts[0]);
+}
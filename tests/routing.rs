@@ -0,0 +1,87 @@
+extern crate videos;
+use std::collections::BTreeMap;
+use videos::types::*;
+use videos::placement::Placement;
+use videos::dense::DenseInstance;
+use videos::routing::{Route, Router, route};
+use videos::score::{score, evaluate};
+
+// Two caches of an endpoint holding the same video, the fastest one serving
+// only 500 requests
+fn instance() -> Instance {
+    let mut cache_info = CacheInfo::new(2, 100);
+    cache_info.set_throughput(CacheId(0), Some(500));
+    let videos = vec![Video::new(VideoId(0), 100), Video::new(VideoId(1), 100)];
+    let mut relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+    relation.insert(CacheId(0), 100);
+    relation.insert(CacheId(1), 200);
+    let endpoints = vec![Endpoint::new(EndpointId(0), 1000, relation)];
+    let requests = vec![Request::new(VideoId(0), EndpointId(0), 1000), Request::new(VideoId(1), EndpointId(0), 500)];
+    Instance::new(cache_info, videos, endpoints, requests)
+}
+
+#[test]
+fn test_route_splits_demand() {
+    let instance = instance();
+    let mut placement = Placement::for_instance(&instance);
+    assert!(placement.try_add(CacheId(0), VideoId(0)));
+    assert!(placement.try_add(CacheId(1), VideoId(0)));
+
+    let routing = route(&instance, &placement);
+    assert_eq!(vec![
+        Route {
+            endpoint_id: EndpointId(0),
+            video_id: VideoId(0),
+            cache_id: CacheId(0),
+            count: 500
+        },
+        Route {
+            endpoint_id: EndpointId(0),
+            video_id: VideoId(0),
+            cache_id: CacheId(1),
+            count: 500
+        }
+    ], routing.routes);
+    assert_eq!(500 * 900 + 500 * 800, routing.saved);
+    assert_eq!(1000, routing.served());
+    assert_eq!(500, routing.load(CacheId(0)));
+}
+
+#[test]
+fn test_router() {
+    let instance = instance();
+    let dense = DenseInstance::from_instance(&instance);
+    let mut placement = Placement::for_instance(&instance);
+    assert!(placement.try_add(CacheId(0), VideoId(0)));
+    assert!(placement.try_add(CacheId(1), VideoId(1)));
+
+    let routing = Router::new(&dense).route(&dense, &instance, &placement);
+    assert_eq!(route(&instance, &placement).routes, routing.routes);
+    assert_eq!(500 * 900 + 500 * 800, routing.saved);
+}
+
+#[test]
+fn test_route_without_limits() {
+    let mut instance = instance();
+    instance.cache_info.set_throughput(CacheId(0), None);
+    let mut placement = Placement::for_instance(&instance);
+    assert!(placement.try_add(CacheId(0), VideoId(0)));
+    assert!(placement.try_add(CacheId(1), VideoId(0)));
+
+    let routing = route(&instance, &placement);
+    assert_eq!(1, routing.routes.len());
+    assert_eq!(1000 * 900, routing.saved);
+    assert_eq!(0, routing.load(CacheId(1)));
+}
+
+#[test]
+fn test_score_with_throughput_limits() {
+    let instance = instance();
+    let mut placement = Placement::for_instance(&instance);
+    assert!(placement.try_add(CacheId(0), VideoId(0)));
+    // 500 * 900 * 1000 / 1500
+    assert_eq!(300000, score(&instance, &placement));
+    let evaluation = evaluate(&instance, &placement);
+    assert_eq!(500, evaluation.requests_served);
+    assert_eq!(1500, evaluation.total_requests);
+}
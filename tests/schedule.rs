@@ -0,0 +1,103 @@
+extern crate videos;
+use videos::types::*;
+use videos::algo::Mode;
+use videos::context::{Context, Limits};
+use videos::parser::parse;
+use videos::placement::Placement;
+use videos::schedule::{SlotValue, copied_bytes, slot_values, schedule_score, plan};
+
+// A cache holding a single video: video 0 is requested in the morning, video 1
+// in the evening, and both a little at noon
+fn sliced() -> Instance {
+    let (cache_info, videos, endpoints, requests) = parse("2 1 4 1 100\n\
+    100 100\n\
+    1000 1\n\
+    0 100\n\
+    0 0 1000 0\n\
+    1 0 10 1\n\
+    0 0 5 1\n\
+    1 0 1000 2").unwrap();
+    Instance::new(cache_info, videos, endpoints, requests)
+}
+
+fn holding(instance: &Instance, video_id: i32) -> Placement {
+    let mut placement = Placement::for_instance(instance);
+    assert!(placement.try_add(CacheId(0), VideoId(video_id)));
+    placement
+}
+
+#[test]
+fn test_slots() {
+    let instance = sliced();
+    assert_eq!(3, instance.slot_count());
+    let noon = instance.for_slot(1);
+    assert_eq!(vec![(VideoId(1), 10), (VideoId(0), 5)],
+               noon.requests.iter().map(|request| (request.video_id, request.count)).collect::<Vec<(VideoId, i32)>>());
+    assert_eq!(1, noon.endpoints.len());
+    assert!(instance.for_slot(3).requests.is_empty());
+}
+
+#[test]
+fn test_copied_bytes() {
+    let instance = sliced();
+    let empty = Placement::for_instance(&instance);
+    assert_eq!(100, copied_bytes(&empty, &holding(&instance, 0)));
+    assert_eq!(0, copied_bytes(&holding(&instance, 0), &holding(&instance, 0)));
+    assert_eq!(100, copied_bytes(&holding(&instance, 0), &holding(&instance, 1)));
+    assert_eq!(0, copied_bytes(&holding(&instance, 0), &empty));
+}
+
+#[test]
+fn test_schedule_score() {
+    let instance = sliced();
+    let placements = vec![holding(&instance, 0), holding(&instance, 1), holding(&instance, 1)];
+    assert_eq!(vec![
+        SlotValue {
+            saved: 900000,
+            copied: 0
+        },
+        SlotValue {
+            saved: 9000,
+            copied: 100
+        },
+        SlotValue {
+            saved: 900000,
+            copied: 0
+        }
+    ], slot_values(&instance, &placements));
+    assert_eq!(1809000, schedule_score(&instance, &placements, 0));
+    assert_eq!(1799000, schedule_score(&instance, &placements, 100));
+}
+
+#[test]
+fn test_plan() {
+    let instance = sliced();
+    let empty = Placement::for_instance(&instance);
+    let placements = plan(&Context::new(), &instance, &empty, Mode::DescentAmend, false, 0, 0);
+    assert_eq!(vec![holding(&instance, 0), holding(&instance, 1), holding(&instance, 1)], placements);
+
+    // Copying video 1 at noon costs more than it saves
+    let placements = plan(&Context::new(), &instance, &empty, Mode::DescentAmend, false, 0, 100);
+    assert_eq!(vec![holding(&instance, 0), holding(&instance, 0), holding(&instance, 1)], placements);
+    assert_eq!(900000 + 4500 + 900000 - 10000, schedule_score(&instance, &placements, 100));
+}
+
+#[test]
+fn test_plan_with_local_search() {
+    let instance = sliced();
+    let empty = Placement::for_instance(&instance);
+    let placements = plan(&Context::new(), &instance, &empty, Mode::Dummy, true, 0, 0);
+    assert_eq!(vec![holding(&instance, 0), holding(&instance, 1), holding(&instance, 1)], placements);
+}
+
+#[test]
+fn test_plan_with_solver_limits() {
+    let instance = sliced();
+    let empty = Placement::for_instance(&instance);
+    let context = Context::new().with_solver_limits("descent-amend", Limits {
+        time_limit: None,
+        max_iterations: Some(0)
+    });
+    let placements = plan(&context, &instance, &empty, Mode::DescentAmend, false, 0, 0);
+    assert_eq!(vec![empty.clone(), empty.clone(), empty.clone()], placements);
+}
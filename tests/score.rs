@@ -0,0 +1,78 @@
+extern crate videos;
+mod common;
+use std::collections::BTreeMap;
+use videos::types::*;
+use videos::placement::Placement;
+use videos::score::{score, validate, evaluate, format_evaluation, Evaluation};
+use common::example;
+
+// Placement of the problem statement
+fn example_placement(instance: &Instance) -> Placement {
+    let mut placement = Placement::for_instance(instance);
+    for &(cache_id, video_id) in &[(0, 2), (1, 3), (1, 1), (2, 0), (2, 1)] {
+        assert!(placement.try_add(CacheId(cache_id), VideoId(video_id)));
+    }
+    placement
+}
+
+#[test]
+fn test_score_example() {
+    let instance = example();
+    assert_eq!(462500, score(&instance, &example_placement(&instance)));
+}
+
+#[test]
+fn test_score_empty() {
+    let instance = example();
+    assert_eq!(0, score(&instance, &Placement::for_instance(&instance)));
+}
+
+#[test]
+fn test_validate() {
+    let instance = example();
+    assert!(validate(&instance, &example_placement(&instance)).is_ok());
+
+    let mut other = example();
+    other.cache_info = CacheInfo::with_capacities(vec![100, 50, 130]);
+    assert_eq!(Err(String::from("Placement built for another instance")),
+               validate(&other, &example_placement(&instance)));
+    other = example();
+    other.videos.pop();
+    assert!(validate(&other, &example_placement(&instance)).is_err());
+}
+
+#[test]
+fn test_evaluate() {
+    let instance = example();
+    let evaluation = evaluate(&instance, &example_placement(&instance));
+    assert_eq!(Evaluation {
+        score: 462500,
+        caches_used: 3,
+        bytes_stored: 260,
+        requests_served: 2500,
+        total_requests: 4000
+    }, evaluation);
+    assert_eq!(0.625, evaluation.served_ratio());
+    assert_eq!("Score: 462500\n\
+                Caches used: 3 / 3\n\
+                Bytes stored: 260 / 300\n\
+                Requests served from a cache: 2500 / 4000 (62.50%)\n", format_evaluation(&instance, &evaluation));
+}
+
+#[test]
+fn test_score_large_values() {
+    // 1000 endpoints each saving 3999 ms on 10000 requests: 39990000000 ms saved
+    let videos = vec![Video::new(VideoId(0), 100)];
+    let endpoints: Vec<Endpoint> = (0..1000).map(|id| {
+        let mut relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+        relation.insert(CacheId(0), 1);
+        Endpoint::new(EndpointId(id), 4000, relation)
+    }).collect();
+    let requests: Vec<Request> = (0..1000).map(|id| Request::new(VideoId(0), EndpointId(id), 10000)).collect();
+    let instance = Instance::new(CacheInfo::new(1, 500), videos, endpoints, requests);
+
+    let mut placement = Placement::for_instance(&instance);
+    assert!(placement.try_add(CacheId(0), VideoId(0)));
+    assert_eq!(3_999_000, score(&instance, &placement));
+    assert_eq!(10_000_000, evaluate(&instance, &placement).total_requests);
+}
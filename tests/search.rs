@@ -0,0 +1,72 @@
+extern crate videos;
+mod common;
+use std::collections::BTreeMap;
+use videos::types::*;
+use videos::placement::Placement;
+use videos::context::Context;
+use videos::score::{score, validate};
+use videos::search::local_search;
+use videos::generator::{GeneratorParams, generate};
+use common::example;
+
+#[test]
+fn test_local_search_from_empty() {
+    let instance = example();
+    let placement = local_search(&Context::new(), &instance, Placement::for_instance(&instance), 0);
+    assert!(validate(&instance, &placement).is_ok());
+    assert!(score(&instance, &placement) >= 462500);
+}
+
+#[test]
+fn test_local_search_swaps() {
+    // Video 0 is only requested by endpoint 1, which has no cache
+    let instance = example();
+    let mut placement = Placement::for_instance(&instance);
+    assert!(placement.try_add(CacheId(0), VideoId(0)));
+    assert!(placement.try_add(CacheId(0), VideoId(1)));
+    let improved = local_search(&Context::new(), &instance, placement.clone(), 0);
+    assert!(validate(&instance, &improved).is_ok());
+    assert!(!improved.contains(CacheId(0), VideoId(0)));
+    assert!(score(&instance, &improved) > score(&instance, &placement));
+}
+
+#[test]
+fn test_local_search_is_reproducible() {
+    let instance = generate(5, &GeneratorParams::new(100, 10, 300, 5, 1500));
+    let first = local_search(&Context::new(), &instance, Placement::for_instance(&instance), 11);
+    assert!(validate(&instance, &first).is_ok());
+    assert_eq!(first, local_search(&Context::new(), &instance, Placement::for_instance(&instance), 11));
+}
+
+#[test]
+fn test_local_search_cancelled() {
+    let instance = example();
+    let context = Context::new();
+    context.cancel();
+    assert!(local_search(&context, &instance, Placement::for_instance(&instance), 0).iter()
+        .all(|(_, videos)| videos.is_empty()));
+}
+
+#[test]
+fn test_local_search_with_throughput_limits() {
+    // Cache 0 can hold both videos but only serve 500 requests, so adding video 0
+    // next to video 1 takes its requests away from cache 1
+    let mut cache_info = CacheInfo::with_capacities(vec![200, 100]);
+    cache_info.set_throughput(CacheId(0), Some(500));
+    let videos = vec![Video::new(VideoId(0), 100), Video::new(VideoId(1), 100)];
+    let mut relation: BTreeMap<CacheId, i32> = BTreeMap::new();
+    relation.insert(CacheId(0), 100);
+    relation.insert(CacheId(1), 200);
+    let endpoints = vec![Endpoint::new(EndpointId(0), 1000, relation)];
+    let requests = vec![Request::new(VideoId(0), EndpointId(0), 1000), Request::new(VideoId(1), EndpointId(0), 500)];
+    let instance = Instance::new(cache_info, videos, endpoints, requests);
+
+    let mut placement = Placement::for_instance(&instance);
+    assert!(placement.try_add(CacheId(0), VideoId(1)));
+    assert!(placement.try_add(CacheId(1), VideoId(0)));
+    let initial_score = score(&instance, &placement);
+    for seed in 0..4 {
+        let searched = local_search(&Context::new(), &instance, placement.clone(), seed);
+        assert!(score(&instance, &searched) >= initial_score);
+    }
+}
@@ -0,0 +1,44 @@
+extern crate videos;
+mod common;
+use videos::stats::{Distribution, stats, format_key_values};
+use common::example;
+
+#[test]
+fn test_distribution() {
+    assert_eq!(Distribution {
+        count: 10,
+        min: 1,
+        max: 10,
+        mean: 5.5,
+        median: 5,
+        p90: 9,
+        total: 55
+    }, Distribution::new(vec![10, 9, 8, 7, 6, 5, 4, 3, 2, 1]));
+
+    let empty = Distribution::new(Vec::new());
+    assert_eq!((0, 0, 0, 0.), (empty.count, empty.min, empty.max, empty.mean));
+}
+
+#[test]
+fn test_stats() {
+    let instance_stats = stats(&example());
+    assert_eq!(5, instance_stats.videos);
+    assert_eq!(1, instance_stats.oversized_videos);
+    assert_eq!(1, instance_stats.unrequested_videos);
+    assert_eq!(4000, instance_stats.total_demand);
+    assert_eq!(1500. / 4000., instance_stats.top_10_percent_share);
+    assert_eq!(5, instance_stats.requests_per_video.count);
+    assert_eq!(1500, instance_stats.requests_per_video.max);
+    assert_eq!((1, 1), (instance_stats.endpoints_per_cache.min, instance_stats.endpoints_per_cache.max));
+    assert_eq!((0, 3), (instance_stats.caches_per_endpoint.min, instance_stats.caches_per_endpoint.max));
+    assert_eq!((700, 900), (instance_stats.latency_saving.min, instance_stats.latency_saving.max));
+    assert_eq!(2400, instance_stats.latency_saving.total);
+}
+
+#[test]
+fn test_key_values() {
+    let key_values = format_key_values(&stats(&example()));
+    assert!(key_values.lines().all(|line| line.split('=').count() == 2));
+    assert!(key_values.contains("\ntotal_demand=4000\n"));
+    assert!(key_values.contains("\nlatency_saving.median=800\n"));
+}
@@ -0,0 +1,106 @@
+extern crate videos;
+use std::collections::BTreeMap;
+use videos::types::*;
+use videos::algo::{algo, Mode};
+use videos::context::Context;
+use videos::parser::parse;
+use videos::placement::Placement;
+use videos::portfolio::{best, reduced_portfolio};
+use videos::score::{score, evaluate};
+use videos::tiers::{effective_latencies, flatten, is_acyclic};
+
+// Two local caches backed by a regional cache 2, which no endpoint reaches directly
+fn tiered() -> Instance {
+    let (cache_info, videos, endpoints, requests) = parse("3 2 3 3 *\n\
+    50 50 100\n\
+    50 50 80\n\
+    1000 1\n\
+    0 100\n\
+    1000 1\n\
+    1 100\n\
+    0 0 100\n\
+    0 1 100\n\
+    2 0 10\n\
+    2\n\
+    0 2 200\n\
+    1 2 300").unwrap();
+    Instance::new(cache_info, videos, endpoints, requests)
+}
+
+#[test]
+fn test_effective_latencies() {
+    let instance = tiered();
+    let latencies = effective_latencies(&instance.cache_info, &instance.endpoints[0]);
+    assert_eq!(vec![(CacheId(0), 100), (CacheId(2), 300)], latencies.into_iter().collect::<Vec<(CacheId, i32)>>());
+    let latencies = effective_latencies(&instance.cache_info, &instance.endpoints[1]);
+    assert_eq!(vec![(CacheId(1), 100), (CacheId(2), 400)], latencies.into_iter().collect::<Vec<(CacheId, i32)>>());
+
+    // A direct link faster than the path through a child wins, and the other way round
+    let mut cache_to_latency: BTreeMap<CacheId, i32> = BTreeMap::new();
+    cache_to_latency.insert(CacheId(0), 100);
+    cache_to_latency.insert(CacheId(1), 50);
+    cache_to_latency.insert(CacheId(2), 320);
+    let endpoint = Endpoint::new(EndpointId(0), 1000, cache_to_latency);
+    let latencies = effective_latencies(&instance.cache_info, &endpoint);
+    assert_eq!(Some(&300), latencies.get(&CacheId(2)));
+    let mut cache_to_latency = endpoint.cache_to_latency.clone();
+    cache_to_latency.insert(CacheId(2), 250);
+    let endpoint = Endpoint::new(EndpointId(0), 1000, cache_to_latency);
+    assert_eq!(Some(&250), effective_latencies(&instance.cache_info, &endpoint).get(&CacheId(2)));
+}
+
+#[test]
+fn test_flatten() {
+    let instance = tiered();
+    assert!(instance.cache_info.has_tiers());
+    let flat = flatten(&instance);
+    assert!(!flat.cache_info.has_tiers());
+    assert_eq!(vec![50, 50, 100], flat.cache_info.caches.iter().map(|cache| cache.capacity).collect::<Vec<i32>>());
+    assert_eq!(Some(&400), flat.endpoints[1].cache_to_latency.get(&CacheId(2)));
+    assert_eq!(instance.requests.len(), flat.requests.len());
+}
+
+#[test]
+fn test_is_acyclic() {
+    let mut cache_info = CacheInfo::new(3, 100);
+    cache_info.set_parent(CacheId(0), CacheId(1), 10);
+    cache_info.set_parent(CacheId(1), CacheId(2), 10);
+    assert!(is_acyclic(&cache_info));
+    cache_info.set_parent(CacheId(2), CacheId(0), 10);
+    assert!(!is_acyclic(&cache_info));
+}
+
+#[test]
+fn test_score_with_tiers() {
+    let instance = tiered();
+    let mut placement = Placement::for_instance(&instance);
+    assert!(placement.try_add(CacheId(2), VideoId(0)));
+    // (100 * 700 + 100 * 600) * 1000 / 210
+    assert_eq!(619047, score(&instance, &placement));
+    assert_eq!(200, evaluate(&instance, &placement).requests_served);
+
+    assert!(placement.try_add(CacheId(0), VideoId(0)));
+    // (100 * 900 + 100 * 600) * 1000 / 210
+    assert_eq!(714285, score(&instance, &placement));
+}
+
+#[test]
+fn test_descent_with_tiers() {
+    let instance = tiered();
+    let placement = algo(Mode::Descent, &Context::new(), instance.cache_info.clone(), instance.videos.clone(),
+                         instance.endpoints.clone(), instance.requests.clone());
+    assert!(placement.contains(CacheId(2), VideoId(0)));
+    assert!(placement.contains(CacheId(0), VideoId(0)));
+    assert!(placement.contains(CacheId(1), VideoId(0)));
+    assert_eq!(857142, score(&instance, &placement));
+}
+
+#[test]
+fn test_reduced_portfolio_keeps_parent_caches() {
+    let instance = tiered();
+    let runs = reduced_portfolio(&Context::new(), &instance, &Placement::for_instance(&instance), &[Mode::BestVideo],
+                                 false, 0).unwrap();
+    let run = best(&runs).unwrap();
+    assert!(run.placement.contains(CacheId(2), VideoId(0)));
+    assert_eq!(score(&instance, &run.placement), run.score);
+}
@@ -0,0 +1,58 @@
+extern crate videos;
+use videos::zip::*;
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+#[test]
+fn test_crc32() {
+    assert_eq!(0, crc32(b""));
+    assert_eq!(0xcbf43926, crc32(b"123456789"));
+    assert_eq!(0x414fa339, crc32(b"The quick brown fox jumps over the lazy dog"));
+}
+
+#[test]
+fn test_empty_archive() {
+    let data = ZipWriter::new().finish().unwrap();
+    assert_eq!(22, data.len());
+    assert_eq!(0x06054b50, u32_at(&data, 0));
+    assert_eq!(0, u16_at(&data, 10));
+}
+
+#[test]
+fn test_archive() {
+    let mut zip = ZipWriter::new();
+    zip.add("a.txt", b"hello").unwrap();
+    zip.add("src/b.rs", b"").unwrap();
+    let data = zip.finish().unwrap();
+
+    // Local header of a.txt, followed by its name and contents
+    assert_eq!(0x04034b50, u32_at(&data, 0));
+    assert_eq!(0, u16_at(&data, 8));
+    assert_eq!(crc32(b"hello"), u32_at(&data, 14));
+    assert_eq!(5, u32_at(&data, 18));
+    assert_eq!(5, u32_at(&data, 22));
+    assert_eq!(b"a.txt", &data[30..35]);
+    assert_eq!(b"hello", &data[35..40]);
+    assert_eq!(0x04034b50, u32_at(&data, 40));
+
+    // End of central directory
+    let end = data.len() - 22;
+    assert_eq!(0x06054b50, u32_at(&data, end));
+    assert_eq!(2, u16_at(&data, end + 10));
+    let size = u32_at(&data, end + 12) as usize;
+    let offset = u32_at(&data, end + 16) as usize;
+    assert_eq!(end, offset + size);
+
+    // Central directory entries point back to the local headers
+    assert_eq!(0x02014b50, u32_at(&data, offset));
+    assert_eq!(0, u32_at(&data, offset + 42));
+    assert_eq!(b"a.txt", &data[offset + 46..offset + 51]);
+    assert_eq!(0x02014b50, u32_at(&data, offset + 51));
+    assert_eq!(40, u32_at(&data, offset + 51 + 42));
+}